@@ -1,202 +1,122 @@
-use async_bench::{async_std_bench, std_thread, thread_crossbeam, thread_flume, tokio_bench};
-use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
-use std::{sync::Arc, time::Duration};
-use tokio::runtime::Runtime as TokioRuntime;
-
-fn bench_one_to_one_unbounded(c: &mut Criterion) {
-    let mut g = c.benchmark_group(format!("1 to 1 (unbounded)"));
-    g.measurement_time(Duration::from_secs(30));
-    let runtime = Arc::new(TokioRuntime::new().unwrap());
-
-    for i in [1, 4, 8, 12, 16, 20, 24].iter() {
-        g.bench_with_input(BenchmarkId::new("async_std", i), i, |b, i| {
-            b.iter(move || {
-                async_std::task::block_on(async {
-                    let mut hdl = async_std_bench::new_one_to_one_unbounded(*i as usize).await;
-                    hdl.start().await;
-                });
-            })
-        });
-
-        let rt = runtime.clone();
-        g.bench_with_input(BenchmarkId::new("tokio", i), i, move |b, i| {
-            b.iter(|| {
-                rt.block_on(async {
-                    let mut hdl = tokio_bench::new_one_to_one_unbounded(*i as usize).await;
-                    hdl.start().await;
-                });
-            })
-        });
-
-        g.bench_with_input(BenchmarkId::new("std", i), i, |b, i| {
-            b.iter(move || {
-                let mut hdl = std_thread::new_one_to_one_channel(*i as usize);
-                hdl.start();
-            })
-        });
-
-        g.bench_with_input(BenchmarkId::new("crossbeam", i), i, |b, i| {
-            b.iter(move || {
-                let mut hdl = thread_crossbeam::new_one_to_one_unbounded(*i as usize);
-                hdl.start();
-            })
-        });
-
-        g.bench_with_input(BenchmarkId::new("flume", i), i, |b, i| {
-            b.iter(move || {
-                let mut hdl = thread_flume::new_one_to_one_unbounded(*i as usize);
-                hdl.start();
-            })
-        });
-    }
-    g.finish();
+use async_bench::{
+    registry::{self, Backend, Runtimes, Scenario},
+    results::Metric,
+};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::time::{Duration, Instant};
+
+// Groups are generated from the scenarios of `registry`, which the binary runs as well.
+// Backends spawn their threads or tasks when they are set up, and the returned run releases them.
+// Only the run is timed, and the cost of spawning is measured by `bench_spawn`.
+
+/// Messages per sender of a run.
+/// Criterion repeats every run many times, so this is smaller than the binary's default.
+const COUNT: usize = 10_000;
+
+/// Scenarios benchmarked by default, whose figures are published.
+/// The others take hours in total, and are benchmarked only if `ALL` is set.
+const DEFAULT_SCENARIOS: &[&str] = &[
+    "one-to-one (unbounded)",
+    "one-to-one (bounded)",
+    "many-to-one",
+    "mutex",
+];
+
+/// Environment variable to benchmark every scenario, and the cost of spawning.
+const ALL: &str = "ASYNC_BENCH_ALL";
+
+fn all() -> bool {
+    std::env::var_os(ALL).is_some()
 }
 
-fn bench_one_to_one_bounded(c: &mut Criterion) {
-    let mut g = c.benchmark_group(format!("1 to 1 (bounded)"));
-    g.measurement_time(Duration::from_secs(30));
-    let runtime = Arc::new(TokioRuntime::new().unwrap());
-
-    for i in [1, 4, 8, 12, 16, 20, 24].iter() {
-        g.bench_with_input(BenchmarkId::new("async_std", i), i, |b, i| {
-            b.iter(move || {
-                async_std::task::block_on(async {
-                    let mut hdl = async_std_bench::new_one_to_one_bounded(*i as usize).await;
-                    hdl.start().await;
-                });
-            })
-        });
-
-        let rt = runtime.clone();
-        g.bench_with_input(BenchmarkId::new("tokio", i), i, move |b, i| {
-            b.iter(|| {
-                rt.block_on(async {
-                    let mut hdl = tokio_bench::new_one_to_one_bounded(*i as usize).await;
-                    hdl.start().await;
-                });
-            })
-        });
-
-        g.bench_with_input(BenchmarkId::new("std", i), i, |b, i| {
-            b.iter(move || {
-                let mut hdl = std_thread::new_one_to_one_sync_channel(*i as usize);
-                hdl.start();
-            })
-        });
-
-        g.bench_with_input(BenchmarkId::new("crossbeam", i), i, |b, i| {
-            b.iter(move || {
-                let mut hdl = thread_crossbeam::new_one_to_one_bounded(*i as usize);
-                hdl.start();
-            })
-        });
-
-        g.bench_with_input(BenchmarkId::new("flume", i), i, |b, i| {
-            b.iter(move || {
-                let mut hdl = thread_flume::new_one_to_one_bounded(*i as usize);
-                hdl.start();
-            })
-        });
+/// Scenarios with many threads or tasks per n take longer to sample.
+fn measurement_time(scenario: &Scenario) -> Duration {
+    match scenario.name.as_str() {
+        "mutex" => Duration::from_secs(120),
+        "many-to-one" | "select" => Duration::from_secs(60),
+        _ => Duration::from_secs(30),
     }
-    g.finish();
 }
 
-fn bench_many_to_one(c: &mut Criterion) {
-    let mut g = c.benchmark_group(format!("many to 1 (bounded)"));
-    g.measurement_time(Duration::from_secs(60));
-    let runtime = Arc::new(TokioRuntime::new().unwrap());
-
-    for i in [4, 8, 12, 16, 20, 24].iter() {
-        g.bench_with_input(BenchmarkId::new("async_std", i), i, |b, i| {
-            b.iter(move || {
-                async_std::task::block_on(async {
-                    let mut hdl = async_std_bench::new_many_to_one_bounded(*i as usize).await;
-                    hdl.start().await;
-                });
-            })
-        });
+/// Benchmark ID of a backend, which is its name unless published under another.
+fn id(backend: &Backend) -> &str {
+    backend.criterion_name.unwrap_or(&backend.name)
+}
 
-        let rt = runtime.clone();
-        g.bench_with_input(BenchmarkId::new("tokio", i), i, move |b, i| {
-            b.iter(|| {
-                rt.block_on(async {
-                    let mut hdl = tokio_bench::new_many_to_one_bounded(*i as usize).await;
-                    hdl.start().await;
+fn bench_scenarios(c: &mut Criterion) {
+    let runtimes = Runtimes::new();
+
+    // Runs of latency scenarios take as long as their senders are paced to,
+    // so timing them tells nothing.
+    for scenario in registry::scenarios(registry::BURSTY)
+        .into_iter()
+        .filter(|s| s.metric == Metric::Throughput)
+        .filter(|s| all() || DEFAULT_SCENARIOS.contains(&s.name.as_str()))
+    {
+        let mut g = c.benchmark_group(scenario.criterion_name.unwrap_or(&scenario.name));
+        g.measurement_time(measurement_time(&scenario));
+
+        let count = (scenario.count)(COUNT);
+        for i in scenario.range {
+            g.throughput(Throughput::Elements((scenario.ops)(*i, count) as u64));
+            for backend in &scenario.backends {
+                // Backends which cannot run with n on this machine are skipped.
+                if let Err(e) = (backend.setup)(&runtimes, *i, count).map(|run| run()) {
+                    eprintln!("{}/{}/{i}: skipped: {e}", scenario.name, backend.name);
+                    continue;
+                }
+                g.bench_with_input(BenchmarkId::new(id(backend), i), i, |b, i| {
+                    b.iter_custom(|iters| {
+                        let mut total = Duration::ZERO;
+                        for _ in 0..iters {
+                            let run = (backend.setup)(&runtimes, *i, count).unwrap();
+                            let t = Instant::now();
+                            black_box(run());
+                            total += t.elapsed();
+                        }
+                        total
+                    })
                 });
-            })
-        });
-
-        g.bench_with_input(BenchmarkId::new("std", i), i, |b, i| {
-            b.iter(move || {
-                let mut hdl = std_thread::new_many_to_one_sync_channel(*i as usize);
-                hdl.start();
-            })
-        });
-
-        g.bench_with_input(BenchmarkId::new("crossbeam", i), i, |b, i| {
-            b.iter(move || {
-                let mut hdl = thread_crossbeam::new_many_to_one_bounded(*i as usize);
-                hdl.start();
-            })
-        });
-
-        g.bench_with_input(BenchmarkId::new("flume", i), i, |b, i| {
-            b.iter(move || {
-                let mut hdl = thread_flume::new_many_to_one_bounded(*i as usize);
-                hdl.start();
-            })
-        });
+            }
+        }
+        g.finish();
     }
-
-    g.finish();
 }
 
-fn bench_mutex(c: &mut Criterion) {
-    let mut g = c.benchmark_group(format!("mutex"));
-    g.measurement_time(Duration::from_secs(120));
-    let runtime = Arc::new(TokioRuntime::new().unwrap());
-
-    for i in [4, 6, 8, 12, 16].iter() {
-        g.bench_with_input(BenchmarkId::new("std", i), i, |b, i| {
-            b.iter(move || {
-                let mut hdl = std_thread::MutexBench::new(*i as usize);
-                hdl.start();
-            })
-        });
-
-        g.bench_with_input(BenchmarkId::new("parking_lot", i), i, |b, i| {
-            b.iter(move || {
-                let mut hdl = std_thread::MutexBenchPackingLot::new(*i as usize);
-                hdl.start();
-            })
-        });
-
-        g.bench_with_input(BenchmarkId::new("async_std", i), i, |b, i| {
-            b.iter(move || {
-                async_std::task::block_on(async {
-                    let mut hdl = async_std_bench::MutexBench::new(*i as usize);
-                    hdl.start().await;
-                });
-            })
-        });
-
-        let rt = runtime.clone();
-        g.bench_with_input(BenchmarkId::new("tokio", i), i, move |b, i| {
-            b.iter(|| {
-                rt.block_on(async {
-                    let mut hdl = tokio_bench::MutexBench::new(*i as usize);
-                    hdl.start().await;
-                });
-            })
-        });
+/// Time to set up the bounded one-to-one backends, i.e., to spawn 2n threads or tasks.
+fn bench_spawn(c: &mut Criterion) {
+    if !all() {
+        return;
     }
+    let mut g = c.benchmark_group("spawn (one-to-one, bounded)");
+    let runtimes = Runtimes::new();
+    let scenario = registry::scenarios(registry::BURSTY)
+        .into_iter()
+        .find(|s| s.name == "one-to-one (bounded)")
+        .unwrap();
+
+    for i in scenario.range {
+        for backend in &scenario.backends {
+            if let Err(e) = (backend.setup)(&runtimes, *i, COUNT).map(|run| run()) {
+                eprintln!("{}/{}/{i}: skipped: {e}", scenario.name, backend.name);
+                continue;
+            }
+            g.bench_with_input(BenchmarkId::new(id(backend), i), i, |b, i| {
+                b.iter_custom(|iters| {
+                    let mut total = Duration::ZERO;
+                    for _ in 0..iters {
+                        let t = Instant::now();
+                        let run = (backend.setup)(&runtimes, *i, COUNT).unwrap();
+                        total += t.elapsed();
+                        run();
+                    }
+                    total
+                })
+            });
+        }
+    }
+    g.finish();
 }
 
-criterion_group!(
-    benches,
-    bench_mutex,
-    bench_many_to_one,
-    bench_one_to_one_unbounded,
-    bench_one_to_one_bounded
-);
+criterion_group!(benches, bench_scenarios, bench_spawn);
 criterion_main!(benches);
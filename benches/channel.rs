@@ -0,0 +1,72 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::sync::mpsc;
+
+// Channels and senders are returned from the routines,
+// so that dropping them is not measured.
+
+fn bench_create(c: &mut Criterion) {
+    let mut g = c.benchmark_group("create");
+
+    g.bench_function("std::sync::mpsc::channel", |b| {
+        b.iter_with_large_drop(mpsc::channel::<usize>)
+    });
+
+    g.bench_function("std::sync::mpsc::sync_channel", |b| {
+        b.iter_with_large_drop(|| mpsc::sync_channel::<usize>(1024))
+    });
+
+    g.bench_function("flume::bounded", |b| {
+        b.iter_with_large_drop(|| flume::bounded::<usize>(1024))
+    });
+
+    g.bench_function("crossbeam::channel::bounded", |b| {
+        b.iter_with_large_drop(|| crossbeam::channel::bounded::<usize>(1024))
+    });
+
+    g.bench_function("async_std::channel::bounded", |b| {
+        b.iter_with_large_drop(|| async_std::channel::bounded::<usize>(1024))
+    });
+
+    g.bench_function("tokio::sync::mpsc::channel", |b| {
+        b.iter_with_large_drop(|| tokio::sync::mpsc::channel::<usize>(1024))
+    });
+
+    g.finish();
+}
+
+fn bench_clone_sender(c: &mut Criterion) {
+    let mut g = c.benchmark_group("clone sender");
+
+    let (tx, _rx) = mpsc::channel::<usize>();
+    g.bench_function("std::sync::mpsc::channel", |b| {
+        b.iter_with_large_drop(|| tx.clone())
+    });
+
+    let (tx, _rx) = mpsc::sync_channel::<usize>(1024);
+    g.bench_function("std::sync::mpsc::sync_channel", |b| {
+        b.iter_with_large_drop(|| tx.clone())
+    });
+
+    let (tx, _rx) = flume::bounded::<usize>(1024);
+    g.bench_function("flume::bounded", |b| b.iter_with_large_drop(|| tx.clone()));
+
+    let (tx, _rx) = crossbeam::channel::bounded::<usize>(1024);
+    g.bench_function("crossbeam::channel::bounded", |b| {
+        b.iter_with_large_drop(|| tx.clone())
+    });
+
+    let (tx, _rx) = async_std::channel::bounded::<usize>(1024);
+    g.bench_function("async_std::channel::bounded", |b| {
+        b.iter_with_large_drop(|| tx.clone())
+    });
+
+    let (tx, _rx) = tokio::sync::mpsc::channel::<usize>(1024);
+    g.bench_function("tokio::sync::mpsc::channel", |b| {
+        b.iter_with_large_drop(|| tx.clone())
+    });
+
+    g.finish();
+}
+
+criterion_group!(benches, bench_create, bench_clone_sender);
+criterion_main!(benches);
@@ -0,0 +1,71 @@
+//! Embed the build environment into the binary, so that results can be
+//! attributed to the toolchain and the dependencies they were measured with.
+
+use std::{env, fs, process::Command};
+
+fn main() {
+    println!("cargo:rerun-if-changed=Cargo.lock");
+
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .and_then(|out| String::from_utf8(out.stdout).ok())
+        .unwrap_or_default();
+    println!("cargo:rustc-env=ASYNC_BENCH_RUSTC={}", version.trim());
+
+    let profile = env::var("PROFILE").unwrap_or_default();
+    println!("cargo:rustc-env=ASYNC_BENCH_PROFILE={profile}");
+
+    let lock = fs::read_to_string("Cargo.lock").unwrap_or_default();
+    println!(
+        "cargo:rustc-env=ASYNC_BENCH_DEPENDENCIES={}",
+        dependencies(&lock).join(",")
+    );
+}
+
+/// `name=version` of every direct dependency of this package in `Cargo.lock`.
+fn dependencies(lock: &str) -> Vec<String> {
+    let packages: Vec<(&str, &str, Vec<&str>)> = lock
+        .split("[[package]]")
+        .skip(1)
+        .filter_map(|pkg| {
+            let field = |key: &str| {
+                pkg.lines()
+                    .find_map(|l| l.strip_prefix(key)?.strip_prefix(" = "))
+                    .map(|v| v.trim_matches('"'))
+            };
+            let deps = match pkg.split_once("dependencies = [") {
+                Some((_, rest)) => rest
+                    .split(']')
+                    .next()
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(|d| d.trim().trim_matches('"'))
+                    .filter(|d| !d.is_empty())
+                    .collect(),
+                None => Vec::new(),
+            };
+            Some((field("name")?, field("version")?, deps))
+        })
+        .collect();
+
+    let name = env::var("CARGO_PKG_NAME").unwrap_or_default();
+    let Some((_, _, deps)) = packages.iter().find(|(n, _, _)| *n == name) else {
+        return Vec::new();
+    };
+
+    deps.iter()
+        .filter_map(|dep| {
+            // An entry is either "name" or "name version" when several versions are locked.
+            let mut it = dep.split(' ');
+            let dep_name = it.next()?;
+            let version = match it.next() {
+                Some(v) => v,
+                None => packages.iter().find(|(n, _, _)| *n == dep_name)?.1,
+            };
+            Some(format!("{dep_name}={version}"))
+        })
+        .collect()
+}
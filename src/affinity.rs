@@ -0,0 +1,237 @@
+//! CPU topology from sysfs, and pinning threads to CPUs.
+
+use std::{fs, io, os::unix::thread::JoinHandleExt, thread::JoinHandle};
+
+/// Where the sender and the receiver of a pair are placed relative to each other.
+#[derive(Debug, Clone, Copy)]
+pub enum Pinning {
+    /// Both threads on the same logical CPU.
+    SameCore,
+    /// Two hardware threads of the same physical core.
+    SmtSibling,
+    /// Different physical cores sharing the L3 cache, i.e., the same CCX on Ryzen.
+    SameL3,
+    /// Cores on different sockets.
+    DifferentSocket,
+}
+
+impl Pinning {
+    pub const ALL: [Pinning; 4] = [
+        Pinning::SameCore,
+        Pinning::SmtSibling,
+        Pinning::SameL3,
+        Pinning::DifferentSocket,
+    ];
+}
+
+#[derive(Debug, Clone)]
+struct Cpu {
+    id: usize,
+    package: usize,
+    core: usize,
+    l3: Option<Vec<usize>>,
+}
+
+impl Cpu {
+    fn same_core(&self, other: &Cpu) -> bool {
+        self.package == other.package && self.core == other.core
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Topology {
+    cpus: Vec<Cpu>,
+}
+
+impl Topology {
+    /// Read the topology of online CPUs which this process may run on
+    /// from `/sys/devices/system/cpu`.
+    pub fn detect() -> io::Result<Self> {
+        let online = fs::read_to_string("/sys/devices/system/cpu/online")?;
+        let allowed = allowed_cpus()?;
+        let mut cpus = Vec::new();
+
+        for id in parse_cpu_list(&online)
+            .into_iter()
+            .filter(|id| allowed.contains(id))
+        {
+            let dir = format!("/sys/devices/system/cpu/cpu{id}");
+            let package = read_usize(&format!("{dir}/topology/physical_package_id"))?;
+            let core = read_usize(&format!("{dir}/topology/core_id"))?;
+            cpus.push(Cpu {
+                id,
+                package,
+                core,
+                l3: read_l3(&dir),
+            });
+        }
+
+        Ok(Topology { cpus })
+    }
+
+    /// One CPU of each physical core.
+    pub fn cores(&self) -> Vec<usize> {
+        let mut v: Vec<&Cpu> = Vec::new();
+        for cpu in self.cpus.iter() {
+            if !v.iter().any(|c| c.same_core(cpu)) {
+                v.push(cpu);
+            }
+        }
+        v.into_iter().map(|c| c.id).collect()
+    }
+
+    /// CPUs of the sender and the receiver of each of n pairs.
+    ///
+    /// Pairs are placed on disjoint CPUs.
+    /// Returns `None` if this machine has fewer than n pairs of CPUs placed as `pinning` requires,
+    /// e.g., `DifferentSocket` on a single-socket machine,
+    /// since sharing CPUs between pairs would measure oversubscription rather than the placement.
+    pub fn pairs(&self, pinning: Pinning, n: usize) -> Option<Vec<(usize, usize)>> {
+        let mut candidates = Vec::new();
+        for a in self.cpus.iter() {
+            for b in self.cpus.iter() {
+                let ok = match pinning {
+                    Pinning::SameCore => a.id == b.id,
+                    Pinning::SmtSibling => a.id < b.id && a.same_core(b),
+                    Pinning::SameL3 => {
+                        a.id < b.id && !a.same_core(b) && a.l3.is_some() && a.l3 == b.l3
+                    }
+                    Pinning::DifferentSocket => a.id < b.id && a.package != b.package,
+                };
+                if ok {
+                    candidates.push((a.id, b.id));
+                }
+            }
+        }
+
+        let mut used = Vec::new();
+        let mut chosen = Vec::new();
+        for (a, b) in candidates {
+            if !used.contains(&a) && !used.contains(&b) {
+                used.push(a);
+                used.push(b);
+                chosen.push((a, b));
+            }
+        }
+
+        if chosen.len() < n {
+            return None;
+        }
+
+        chosen.truncate(n);
+        Some(chosen)
+    }
+}
+
+/// Pin a spawned thread to a CPU, using `pthread_setaffinity_np`,
+/// which is `sched_setaffinity` of the thread on Linux.
+pub fn pin<T>(th: &JoinHandle<T>, cpu: usize) -> io::Result<()> {
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_SET(cpu, &mut set);
+        let err = libc::pthread_setaffinity_np(
+            th.as_pthread_t(),
+            std::mem::size_of::<libc::cpu_set_t>(),
+            &set,
+        );
+        if err != 0 {
+            return Err(io::Error::from_raw_os_error(err));
+        }
+    }
+    Ok(())
+}
+
+/// CPUs in the affinity mask of this process, e.g., restricted by a cgroup cpuset or `taskset`.
+fn allowed_cpus() -> io::Result<Vec<usize>> {
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        if libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set) != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok((0..libc::CPU_SETSIZE as usize)
+            .filter(|cpu| libc::CPU_ISSET(*cpu, &set))
+            .collect())
+    }
+}
+
+fn read_usize(path: &str) -> io::Result<usize> {
+    fs::read_to_string(path)?
+        .trim()
+        .parse()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// CPUs sharing the L3 cache with the CPU of `dir`.
+fn read_l3(dir: &str) -> Option<Vec<usize>> {
+    for entry in fs::read_dir(format!("{dir}/cache")).ok()?.flatten() {
+        let path = entry.path();
+        if read_usize(&format!("{}/level", path.display())).ok() == Some(3) {
+            let list = fs::read_to_string(path.join("shared_cpu_list")).ok()?;
+            return Some(parse_cpu_list(&list));
+        }
+    }
+    None
+}
+
+/// Parse a list such as "0-3,8,10-11".
+fn parse_cpu_list(s: &str) -> Vec<usize> {
+    let mut v = Vec::new();
+    for range in s.trim().split(',').filter(|r| !r.is_empty()) {
+        match range.split_once('-') {
+            Some((a, b)) => {
+                if let (Ok(a), Ok(b)) = (a.parse::<usize>(), b.parse::<usize>()) {
+                    v.extend(a..=b);
+                }
+            }
+            None => {
+                if let Ok(a) = range.parse() {
+                    v.push(a);
+                }
+            }
+        }
+    }
+    v
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A socket of 2 cores with 2 hardware threads each, numbered as Linux does:
+    /// CPUs 0 and 2 on core 0, and 1 and 3 on core 1, all sharing the L3 cache.
+    fn two_cores() -> Topology {
+        let cpus = (0..4)
+            .map(|id| Cpu {
+                id,
+                package: 0,
+                core: id % 2,
+                l3: Some(vec![0, 1, 2, 3]),
+            })
+            .collect();
+        Topology { cpus }
+    }
+
+    #[test]
+    fn cpu_lists() {
+        assert_eq!(parse_cpu_list("0-3,8-11\n"), vec![0, 1, 2, 3, 8, 9, 10, 11]);
+        assert_eq!(parse_cpu_list("5"), vec![5]);
+        assert_eq!(parse_cpu_list("0,2-3"), vec![0, 2, 3]);
+    }
+
+    #[test]
+    fn pairs_are_placed_as_pinned() {
+        let t = two_cores();
+        assert_eq!(t.cores(), vec![0, 1]);
+        assert_eq!(t.pairs(Pinning::SmtSibling, 2), Some(vec![(0, 2), (1, 3)]));
+        assert_eq!(t.pairs(Pinning::SameL3, 2), Some(vec![(0, 1), (2, 3)]));
+        assert_eq!(t.pairs(Pinning::SameCore, 1), Some(vec![(0, 0)]));
+    }
+
+    #[test]
+    fn too_few_cpus_for_the_pinning_give_none() {
+        let t = two_cores();
+        assert_eq!(t.pairs(Pinning::SmtSibling, 3), None);
+        assert_eq!(t.pairs(Pinning::SameL3, 3), None);
+        assert_eq!(t.pairs(Pinning::DifferentSocket, 1), None);
+    }
+}
@@ -1,14 +1,20 @@
+use crate::{
+    latency::Latency,
+    workload::{Bursts, Workload},
+};
 use async_barrier::Barrier;
 use async_std::{
     channel::{self, Receiver, Sender},
     sync::Mutex,
     task::JoinHandle,
 };
+use futures::StreamExt;
 use std::sync::Arc;
 
 pub struct OneToOneAsync {
     handler: Vec<JoinHandle<usize>>,
     barrier: Arc<Barrier>,
+    bursts: Bursts,
 }
 
 impl OneToOneAsync {
@@ -20,20 +26,39 @@ impl OneToOneAsync {
         }
     }
 
-    pub fn new(n: usize, f: fn() -> (Sender<usize>, Receiver<usize>)) -> Self {
+    /// Latency of draining every burst of the last run, if the workload is bursty.
+    pub fn drain_latency(&self) -> Option<Latency> {
+        self.bursts.latency()
+    }
+
+    pub fn new(n: usize, count: usize, f: fn() -> (Sender<usize>, Receiver<usize>)) -> Self {
+        Self::new_with(n, count, f, Workload::Continuous)
+    }
+
+    pub fn new_with(
+        n: usize,
+        count: usize,
+        f: fn() -> (Sender<usize>, Receiver<usize>),
+        workload: Workload,
+    ) -> Self {
         let mut v = Vec::new();
         let barrier = async_barrier::Barrier::new(2 * n + 1);
         let barrier = Arc::new(barrier);
+        let bursts = Bursts::new(workload);
 
         for _ in 0..n {
             let (tx, rx) = f();
 
             // Create a sender.
             let bar = barrier.clone();
+            let mut stamp = bursts.stamp();
             let th = async_std::task::spawn(async move {
                 bar.wait().await;
-                for _ in 0..crate::MAX_COUNT {
-                    tx.send(1).await.unwrap();
+                for i in 0..count {
+                    tx.send(stamp.message(i)).await.unwrap();
+                    if let Some(gap) = workload.gap_after(i) {
+                        async_std::task::sleep(gap).await;
+                    }
                 }
                 0
             });
@@ -41,14 +66,14 @@ impl OneToOneAsync {
 
             // Create a receiver.
             let bar = barrier.clone();
+            let mut drain = bursts.drain();
             let th = async_std::task::spawn(async move {
                 bar.wait().await;
-                let mut cnt = 0;
-                for _ in 0..crate::MAX_COUNT {
-                    let n = rx.recv().await.unwrap();
-                    cnt += n;
+                for _ in 0..count {
+                    drain.received(rx.recv().await.unwrap());
                 }
-                cnt
+                drain.finish();
+                0
             });
             v.push(th);
         }
@@ -56,24 +81,34 @@ impl OneToOneAsync {
         OneToOneAsync {
             handler: v,
             barrier,
+            bursts,
         }
     }
 }
 
-pub async fn new_one_to_one_unbounded(n: usize) -> OneToOneAsync {
-    OneToOneAsync::new(n, channel::unbounded)
+pub async fn new_one_to_one_unbounded(n: usize, count: usize) -> OneToOneAsync {
+    OneToOneAsync::new(n, count, channel::unbounded)
 }
 
-pub async fn new_one_to_one_bounded(n: usize) -> OneToOneAsync {
+pub async fn new_one_to_one_bounded(n: usize, count: usize) -> OneToOneAsync {
+    new_one_to_one_bounded_with(n, count, Workload::Continuous).await
+}
+
+pub async fn new_one_to_one_bounded_with(
+    n: usize,
+    count: usize,
+    workload: Workload,
+) -> OneToOneAsync {
     fn mkch() -> (Sender<usize>, Receiver<usize>) {
         channel::bounded(1024)
     }
-    OneToOneAsync::new(n, mkch)
+    OneToOneAsync::new_with(n, count, mkch, workload)
 }
 
 pub struct ManyToOneAsync {
     handler: Vec<JoinHandle<usize>>,
     barrier: Arc<Barrier>,
+    bursts: Bursts,
 }
 
 impl ManyToOneAsync {
@@ -85,33 +120,140 @@ impl ManyToOneAsync {
         }
     }
 
-    pub fn new(n: usize, f: fn() -> (Sender<usize>, Receiver<usize>)) -> Self {
+    /// Latency of draining every burst of the last run, if the workload is bursty.
+    pub fn drain_latency(&self) -> Option<Latency> {
+        self.bursts.latency()
+    }
+
+    pub fn new_with(
+        n: usize,
+        count: usize,
+        f: fn() -> (Sender<usize>, Receiver<usize>),
+        workload: Workload,
+    ) -> Self {
         let mut v = Vec::new();
         let barrier = async_barrier::Barrier::new(n + 2);
         let barrier = Arc::new(barrier);
+        let bursts = Bursts::new(workload);
         let (tx, rx) = f();
-        let max_count = crate::MAX_COUNT / 10;
 
         // Create a receiver.
         let bar = barrier.clone();
+        let mut drain = bursts.drain();
         let th = async_std::task::spawn(async move {
+            bar.wait().await;
+            for _ in 0..(count * n) {
+                drain.received(rx.recv().await.unwrap());
+            }
+            drain.finish();
+            0
+        });
+        v.push(th);
+
+        for _ in 0..n {
+            // Create a sender.
+            let bar = barrier.clone();
+            let ch = tx.clone();
+            let mut stamp = bursts.stamp();
+            let th = async_std::task::spawn(async move {
+                bar.wait().await;
+                for i in 0..count {
+                    ch.send(stamp.message(i)).await.unwrap();
+                    if let Some(gap) = workload.gap_after(i) {
+                        async_std::task::sleep(gap).await;
+                    }
+                }
+                0
+            });
+            v.push(th);
+        }
+
+        ManyToOneAsync {
+            handler: v,
+            barrier,
+            bursts,
+        }
+    }
+
+    /// A receiver waits on k channels, each of which has a sender.
+    ///
+    /// `futures::select!` takes a fixed number of branches, so the receiver
+    /// selects over the channels with `futures::stream::select_all` instead.
+    /// `new_select4` uses the macro itself with k = 4.
+    pub fn new_select(k: usize, count: usize, f: fn() -> (Sender<usize>, Receiver<usize>)) -> Self {
+        let mut v = Vec::new();
+        let barrier = async_barrier::Barrier::new(k + 2);
+        let barrier = Arc::new(barrier);
+        let mut txs = Vec::new();
+        let mut rxs = Vec::new();
+
+        for _ in 0..k {
+            let (tx, rx) = f();
+            txs.push(tx);
+            rxs.push(rx);
+        }
+
+        for tx in txs.iter() {
+            // Create a sender.
+            let bar = barrier.clone();
+            let ch = tx.clone();
+            let th = async_std::task::spawn(async move {
+                bar.wait().await;
+                for _ in 0..count {
+                    ch.send(1).await.unwrap();
+                }
+                0
+            });
+            v.push(th);
+        }
+
+        // Create a receiver.
+        let bar = barrier.clone();
+        let th = async_std::task::spawn(async move {
+            // Keep the senders alive so that no channel is disconnected while selecting.
+            let _txs = txs;
+
             bar.wait().await;
             let mut cnt = 0;
-            for _ in 0..(max_count * n) {
-                let n = rx.recv().await.unwrap();
+            let mut rx = futures::stream::select_all(rxs);
+            for _ in 0..(count * k) {
+                let n = rx.next().await.unwrap();
                 cnt += n;
             }
             cnt
         });
         v.push(th);
 
-        for _ in 0..n {
+        ManyToOneAsync {
+            handler: v,
+            barrier,
+            bursts: Bursts::default(),
+        }
+    }
+}
+
+impl ManyToOneAsync {
+    /// A receiver waits on 4 channels with `futures::select!`, each of which has a sender.
+    pub fn new_select4(count: usize, f: fn() -> (Sender<usize>, Receiver<usize>)) -> Self {
+        let mut v = Vec::new();
+        let barrier = async_barrier::Barrier::new(4 + 2);
+        let barrier = Arc::new(barrier);
+        let mut txs = Vec::new();
+        let mut rxs = Vec::new();
+
+        for _ in 0..4 {
+            let (tx, rx) = f();
+            txs.push(tx);
+            rxs.push(rx);
+        }
+
+        for tx in txs.iter() {
             // Create a sender.
             let bar = barrier.clone();
             let ch = tx.clone();
             let th = async_std::task::spawn(async move {
                 bar.wait().await;
-                for _ in 0..max_count {
+                for _ in 0..count {
                     ch.send(1).await.unwrap();
                 }
                 0
@@ -119,18 +261,63 @@ impl ManyToOneAsync {
             v.push(th);
         }
 
+        // Create a receiver.
+        let bar = barrier.clone();
+        let [mut a, mut b, mut c, mut d]: [Receiver<usize>; 4] = rxs.try_into().unwrap();
+        let th = async_std::task::spawn(async move {
+            // Keep the senders alive so that no channel is disconnected while selecting.
+            let _txs = txs;
+
+            bar.wait().await;
+            let mut cnt = 0;
+            for _ in 0..(count * 4) {
+                let n = futures::select! {
+                    n = a.next() => n,
+                    n = b.next() => n,
+                    n = c.next() => n,
+                    n = d.next() => n,
+                };
+                cnt += n.unwrap();
+            }
+            cnt
+        });
+        v.push(th);
+
         ManyToOneAsync {
             handler: v,
             barrier,
+            bursts: Bursts::default(),
         }
     }
 }
 
-pub async fn new_many_to_one_bounded(n: usize) -> ManyToOneAsync {
+pub async fn new_many_to_one_bounded(n: usize, count: usize) -> ManyToOneAsync {
+    new_many_to_one_bounded_with(n, count, Workload::Continuous).await
+}
+
+pub async fn new_many_to_one_bounded_with(
+    n: usize,
+    count: usize,
+    workload: Workload,
+) -> ManyToOneAsync {
+    fn mkch() -> (Sender<usize>, Receiver<usize>) {
+        channel::bounded(1024)
+    }
+    ManyToOneAsync::new_with(n, count, mkch, workload)
+}
+
+pub async fn new_select_bounded(k: usize, count: usize) -> ManyToOneAsync {
+    fn mkch() -> (Sender<usize>, Receiver<usize>) {
+        channel::bounded(1024)
+    }
+    ManyToOneAsync::new_select(k, count, mkch)
+}
+
+pub async fn new_select4_bounded(count: usize) -> ManyToOneAsync {
     fn mkch() -> (Sender<usize>, Receiver<usize>) {
         channel::bounded(1024)
     }
-    ManyToOneAsync::new(n, mkch)
+    ManyToOneAsync::new_select4(count, mkch)
 }
 
 pub struct MutexBench {
@@ -139,7 +326,7 @@ pub struct MutexBench {
 }
 
 impl MutexBench {
-    pub fn new(n: usize) -> Self {
+    pub fn new(n: usize, count: usize) -> Self {
         let mut v = Vec::new();
         let barrier = Arc::new(Barrier::new(n + 1));
         let shared = Arc::new(Mutex::new(0));
@@ -149,7 +336,7 @@ impl MutexBench {
             let n = shared.clone();
             let th = async_std::task::spawn(async move {
                 bar.wait().await;
-                for _ in 0..crate::MAX_COUNT {
+                for _ in 0..count {
                     let mut guard = n.lock().await;
                     *guard += 1;
                 }
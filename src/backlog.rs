@@ -0,0 +1,247 @@
+//! One-to-one communications over unbounded channels whose consumer is slower
+//! than the producer, so that messages pile up in the queue.
+//!
+//! The producer sends as fast as it can, while the consumer works on every
+//! message or pauses periodically.
+//! The depth of the queue is sampled every `SAMPLE_PERIOD` from counters of
+//! sent and received messages, so backends are compared on the same terms.
+//!
+//! Async backends are driven from threads with their blocking API,
+//! because what matters here is how their queues grow.
+
+use crate::std_thread::{Rx, Tx};
+use crossbeam::utils::CachePadded;
+use std::{
+    cell::RefCell,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Barrier,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+const SAMPLE_PERIOD: Duration = Duration::from_millis(1);
+
+/// How the consumer slows down.
+#[derive(Debug, Clone, Copy)]
+pub enum Consumer {
+    /// Spin for the given time on every message.
+    Work(Duration),
+    /// Stop receiving for `pause` after every `every` messages.
+    Pause { every: usize, pause: Duration },
+}
+
+impl Consumer {
+    fn consume(&self, i: usize) {
+        match *self {
+            Consumer::Work(work) => {
+                let t = Instant::now();
+                while t.elapsed() < work {
+                    std::hint::spin_loop();
+                }
+            }
+            Consumer::Pause { every, pause } => {
+                if (i + 1).is_multiple_of(every) {
+                    std::thread::sleep(pause);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Report {
+    /// Depth of the queue sampled over time since the start.
+    pub depth: Vec<(Duration, usize)>,
+    pub peak_depth: usize,
+    /// Time until the producer sent every message.
+    pub produce: Duration,
+    /// Time the consumer took to receive the rest after the producer finished.
+    pub drain: Duration,
+}
+
+#[derive(Default)]
+struct Counters {
+    sent: CachePadded<AtomicUsize>,
+    received: CachePadded<AtomicUsize>,
+}
+
+pub struct Backlog {
+    /// The producer returns when it started and finished sending.
+    producer: Option<JoinHandle<(Instant, Instant)>>,
+    consumer: Option<JoinHandle<Instant>>,
+    barrier: Arc<Barrier>,
+    counters: Arc<Counters>,
+    count: usize,
+}
+
+impl Backlog {
+    /// A pair exchanging `count` messages.
+    pub fn new(count: usize, consumer: Consumer, mkch: fn() -> (Tx, Rx)) -> Self {
+        let (tx, rx) = mkch();
+        let barrier = Arc::new(Barrier::new(3));
+        let counters = Arc::new(Counters::default());
+
+        // Create a producer.
+        let bar = barrier.clone();
+        let cnt = counters.clone();
+        let producer = std::thread::spawn(move || {
+            bar.wait();
+            let start = Instant::now();
+            for _ in 0..count {
+                tx(1);
+                cnt.sent.fetch_add(1, Ordering::Relaxed);
+            }
+            (start, Instant::now())
+        });
+
+        // Create a consumer.
+        let bar = barrier.clone();
+        let cnt = counters.clone();
+        let consumer = std::thread::spawn(move || {
+            bar.wait();
+            for i in 0..count {
+                rx();
+                cnt.received.fetch_add(1, Ordering::Relaxed);
+                consumer.consume(i);
+            }
+            Instant::now()
+        });
+
+        Self {
+            producer: Some(producer),
+            consumer: Some(consumer),
+            barrier,
+            counters,
+            count,
+        }
+    }
+
+    /// Run the pair, sampling the depth of the queue until it is drained.
+    pub fn start(&mut self) -> Report {
+        self.barrier.wait();
+        let start = Instant::now();
+
+        let mut depth = Vec::new();
+        loop {
+            let received = self.counters.received.load(Ordering::Relaxed);
+            let sent = self.counters.sent.load(Ordering::Relaxed);
+            depth.push((start.elapsed(), sent.saturating_sub(received)));
+            if received == self.count {
+                break;
+            }
+            std::thread::sleep(SAMPLE_PERIOD);
+        }
+
+        let (started, produced) = self.producer.take().unwrap().join().unwrap();
+        let consumed = self.consumer.take().unwrap().join().unwrap();
+
+        Report {
+            peak_depth: depth.iter().map(|(_, d)| *d).max().unwrap_or(0),
+            depth,
+            produce: produced - started,
+            drain: consumed.saturating_duration_since(produced),
+        }
+    }
+}
+
+pub fn new_backlog_channel(count: usize, consumer: Consumer) -> Backlog {
+    fn mkch() -> (Tx, Rx) {
+        let (tx, rx) = std::sync::mpsc::channel();
+        (
+            Box::new(move |x| tx.send(x).unwrap()),
+            Box::new(move || rx.recv().unwrap()),
+        )
+    }
+
+    Backlog::new(count, consumer, mkch)
+}
+
+pub fn new_backlog_flume(count: usize, consumer: Consumer) -> Backlog {
+    fn mkch() -> (Tx, Rx) {
+        let (tx, rx) = flume::unbounded();
+        (
+            Box::new(move |x| tx.send(x).unwrap()),
+            Box::new(move || rx.recv().unwrap()),
+        )
+    }
+
+    Backlog::new(count, consumer, mkch)
+}
+
+pub fn new_backlog_crossbeam(count: usize, consumer: Consumer) -> Backlog {
+    fn mkch() -> (Tx, Rx) {
+        let (tx, rx) = crossbeam::channel::unbounded();
+        (
+            Box::new(move |x| tx.send(x).unwrap()),
+            Box::new(move || rx.recv().unwrap()),
+        )
+    }
+
+    Backlog::new(count, consumer, mkch)
+}
+
+pub fn new_backlog_kanal(count: usize, consumer: Consumer) -> Backlog {
+    fn mkch() -> (Tx, Rx) {
+        let (tx, rx) = kanal::unbounded();
+        (
+            Box::new(move |x| tx.send(x).unwrap()),
+            Box::new(move || rx.recv().unwrap()),
+        )
+    }
+
+    Backlog::new(count, consumer, mkch)
+}
+
+pub fn new_backlog_async_std(count: usize, consumer: Consumer) -> Backlog {
+    fn mkch() -> (Tx, Rx) {
+        let (tx, rx) = async_std::channel::unbounded();
+        (
+            Box::new(move |x| tx.try_send(x).unwrap()),
+            Box::new(move || async_std::task::block_on(rx.recv()).unwrap()),
+        )
+    }
+
+    Backlog::new(count, consumer, mkch)
+}
+
+pub fn new_backlog_tokio(count: usize, consumer: Consumer) -> Backlog {
+    fn mkch() -> (Tx, Rx) {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let rx = RefCell::new(rx);
+        (
+            Box::new(move |x| tx.send(x).unwrap()),
+            Box::new(move || rx.borrow_mut().blocking_recv().unwrap()),
+        )
+    }
+
+    Backlog::new(count, consumer, mkch)
+}
+
+pub fn new_backlog_smol(count: usize, consumer: Consumer) -> Backlog {
+    fn mkch() -> (Tx, Rx) {
+        let (tx, rx) = async_channel::unbounded();
+        (
+            Box::new(move |x| tx.try_send(x).unwrap()),
+            Box::new(move || rx.recv_blocking().unwrap()),
+        )
+    }
+
+    Backlog::new(count, consumer, mkch)
+}
+
+pub fn new_backlog_futures(count: usize, consumer: Consumer) -> Backlog {
+    fn mkch() -> (Tx, Rx) {
+        use futures::StreamExt;
+
+        let (tx, rx) = futures::channel::mpsc::unbounded();
+        let rx = RefCell::new(rx);
+        (
+            Box::new(move |x| tx.unbounded_send(x).unwrap()),
+            Box::new(move || futures::executor::block_on(rx.borrow_mut().next()).unwrap()),
+        )
+    }
+
+    Backlog::new(count, consumer, mkch)
+}
@@ -0,0 +1,330 @@
+//! Named baselines, and comparison of a run against one of them.
+//!
+//! A baseline is a result set saved under `baselines/<name>.json`.
+//! Every point of the baseline, i.e., (scenario, backend, n), is compared with the same point of the run:
+//! throughput, or p99 latency if the scenario measures latency.
+//! A change is flagged only if it is beyond the noise of the two measurements,
+//! and a point missing from the run is flagged as well.
+
+use crate::results::{Axis, Metric, Record, ResultSet};
+use std::{
+    collections::BTreeMap,
+    fmt, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+pub const DIR: &str = "baselines";
+
+/// Changes below 5% are never flagged.
+pub const DEFAULT_THRESHOLD: f64 = 0.05;
+
+/// How many standard deviations of the combined noise a change must exceed.
+pub const SIGMAS: f64 = 3.0;
+
+pub fn path(name: &str) -> PathBuf {
+    Path::new(DIR).join(format!("{name}.json"))
+}
+
+pub fn save(results: &ResultSet, name: &str) -> io::Result<PathBuf> {
+    let path = path(name);
+    results.save(&path)?;
+    Ok(path)
+}
+
+pub fn load(name: &str) -> io::Result<ResultSet> {
+    ResultSet::load(path(name))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Regressed,
+    Improved,
+    Unchanged,
+    /// In the baseline, but not in the run, e.g., because a backend was skipped or failed.
+    Missing,
+}
+
+impl Verdict {
+    pub fn is_failure(self) -> bool {
+        matches!(self, Verdict::Regressed | Verdict::Missing)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    pub scenario: String,
+    pub backend: String,
+    pub n: usize,
+    pub metric: Metric,
+    /// `Record::value()` of the baseline and of the current run, if the run has this point.
+    pub baseline: f64,
+    pub current: Option<f64>,
+    /// Relative change of the value, e.g., -0.1 is 10% less ops/s or 10% lower latency.
+    pub change: f64,
+    /// Relative change beyond which this point is flagged.
+    pub threshold: f64,
+    /// Whether both runs repeated this point, so that its noise is known.
+    pub repeated: bool,
+    pub verdict: Verdict,
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} / {} / n = {}: ", self.scenario, self.backend, self.n)?;
+        let Some(current) = self.current else {
+            return write!(f, "missing from this run");
+        };
+        match self.metric {
+            Metric::Latency => write!(
+                f,
+                "{:>10.3?} -> {:>10.3?} [p99]",
+                Duration::from_secs_f64(self.baseline),
+                Duration::from_secs_f64(current)
+            )?,
+            _ => write!(
+                f,
+                "{:>10} -> {:>10} [ops/s]",
+                self.baseline as usize, current as usize
+            )?,
+        }
+        write!(
+            f,
+            ", {:+.1}% (threshold {:.1}%)",
+            self.change * 100.0,
+            self.threshold * 100.0
+        )
+    }
+}
+
+/// Whether a record is compared with baselines.
+/// Metrics specific to a scenario are not,
+/// and neither are sweeps of target rates, which reach different rates before saturating in every run.
+fn is_compared(r: &Record) -> bool {
+    r.metric != Metric::Other && r.axis != Axis::Rate
+}
+
+/// Compare every point of `baseline` with the same point of `current`.
+///
+/// The threshold of a point is `SIGMAS` times the combined coefficient of variation
+/// of the repeated samples of both runs, but at least `min_threshold`.
+/// Points measured once have no spread, so only `min_threshold` applies to them.
+pub fn compare(baseline: &ResultSet, current: &ResultSet, min_threshold: f64) -> Vec<Comparison> {
+    let cur: BTreeMap<_, _> = current
+        .records
+        .iter()
+        .filter(|r| is_compared(r))
+        .map(|r| ((r.scenario.as_str(), r.backend.as_str(), r.n), r))
+        .collect();
+
+    let mut v = Vec::new();
+    for base in baseline.records.iter().filter(|r| is_compared(r)) {
+        let Some(baseline) = base.value().filter(|x| *x > 0.0) else {
+            continue;
+        };
+        let mut c = Comparison {
+            scenario: base.scenario.clone(),
+            backend: base.backend.clone(),
+            n: base.n,
+            metric: base.metric,
+            baseline,
+            current: None,
+            change: 0.0,
+            threshold: min_threshold,
+            repeated: false,
+            verdict: Verdict::Missing,
+        };
+
+        let key = (base.scenario.as_str(), base.backend.as_str(), base.n);
+        let Some((cur, current)) = cur.get(&key).and_then(|r| Some((*r, r.value()?))) else {
+            v.push(c);
+            continue;
+        };
+
+        let change = current / baseline - 1.0;
+        let noise = (cv(base).powi(2) + cv(cur).powi(2)).sqrt();
+        let threshold = (SIGMAS * noise).max(min_threshold);
+        // Positive if better, i.e., more ops/s or lower latency.
+        let gain = match base.metric {
+            Metric::Latency => -change,
+            _ => change,
+        };
+        c.verdict = if gain < -threshold {
+            Verdict::Regressed
+        } else if gain > threshold {
+            Verdict::Improved
+        } else {
+            Verdict::Unchanged
+        };
+        c.current = Some(current);
+        c.change = change;
+        c.threshold = threshold;
+        c.repeated = base.samples.len() > 1 && cur.samples.len() > 1;
+        v.push(c);
+    }
+    v
+}
+
+/// Coefficient of variation of the samples of a record, or 0 if it was measured once.
+fn cv(r: &Record) -> f64 {
+    let Some(stddev) = r.stddev() else {
+        return 0.0;
+    };
+    let mean = r.samples.iter().sum::<f64>() / r.samples.len() as f64;
+    if mean > 0.0 {
+        stddev / mean
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{environment::Environment, latency::Latency};
+
+    const MIN: f64 = DEFAULT_THRESHOLD;
+
+    /// A throughput record of 1000 ops at `ops_per_sec`, repeated as `samples`.
+    fn throughput(backend: &str, ops_per_sec: f64, samples: &[f64]) -> Record {
+        let secs = Duration::from_secs_f64(1000.0 / ops_per_sec);
+        let mut r = Record::new("s", backend, 1, 1000, secs);
+        r.samples = samples.to_vec();
+        r
+    }
+
+    /// A latency record of p99 `micros`.
+    fn latency(backend: &str, micros: u64) -> Record {
+        let latency = Latency {
+            p99: Duration::from_micros(micros),
+            ..Latency::default()
+        };
+        Record::latency("s", backend, 1, 1000, latency)
+    }
+
+    fn results(records: Vec<Record>) -> ResultSet {
+        let mut results = ResultSet::new(Environment::default(), 1);
+        results.records = records;
+        results
+    }
+
+    fn verdicts(baseline: Vec<Record>, current: Vec<Record>) -> Vec<(String, Verdict)> {
+        compare(&results(baseline), &results(current), MIN)
+            .into_iter()
+            .map(|c| (c.backend, c.verdict))
+            .collect()
+    }
+
+    fn verdict(baseline: Record, current: Record) -> Verdict {
+        compare(&results(vec![baseline]), &results(vec![current]), MIN)[0].verdict
+    }
+
+    #[test]
+    fn less_throughput_regresses_and_more_improves() {
+        let baseline = vec![
+            throughput("a", 100.0, &[]),
+            throughput("b", 100.0, &[]),
+            throughput("c", 100.0, &[]),
+        ];
+        let current = vec![
+            throughput("a", 80.0, &[]),
+            throughput("b", 120.0, &[]),
+            throughput("c", 97.0, &[]),
+        ];
+        assert_eq!(
+            verdicts(baseline, current),
+            vec![
+                ("a".to_string(), Verdict::Regressed),
+                ("b".to_string(), Verdict::Improved),
+                ("c".to_string(), Verdict::Unchanged),
+            ]
+        );
+    }
+
+    #[test]
+    fn more_latency_regresses_and_less_improves() {
+        assert_eq!(
+            verdict(latency("a", 100), latency("a", 120)),
+            Verdict::Regressed
+        );
+        assert_eq!(
+            verdict(latency("a", 100), latency("a", 80)),
+            Verdict::Improved
+        );
+        assert_eq!(
+            verdict(latency("a", 100), latency("a", 103)),
+            Verdict::Unchanged
+        );
+    }
+
+    #[test]
+    fn points_missing_from_the_run_fail() {
+        let c = compare(
+            &results(vec![
+                throughput("a", 100.0, &[]),
+                throughput("b", 100.0, &[]),
+            ]),
+            &results(vec![throughput("a", 100.0, &[])]),
+            MIN,
+        );
+        assert_eq!(c.len(), 2);
+        assert_eq!(c[1].backend, "b");
+        assert_eq!(c[1].verdict, Verdict::Missing);
+        assert!(c[1].verdict.is_failure());
+        assert_eq!(c[1].current, None);
+    }
+
+    #[test]
+    fn noise_raises_the_threshold_above_the_minimum() {
+        // cv of about 10% in both runs.
+        let noisy = [90.0, 100.0, 110.0];
+        let c = compare(
+            &results(vec![throughput("a", 100.0, &noisy)]),
+            &results(vec![throughput("a", 80.0, &noisy)]),
+            MIN,
+        );
+        let expected = SIGMAS * (2.0 * 0.1f64.powi(2)).sqrt();
+        assert!((c[0].threshold - expected).abs() < 1e-9);
+        assert!(c[0].repeated);
+        assert_eq!(c[0].verdict, Verdict::Unchanged);
+
+        // cv of 0.1% in both runs is below the minimum.
+        let quiet = [99.9, 100.0, 100.1];
+        let c = compare(
+            &results(vec![throughput("a", 100.0, &quiet)]),
+            &results(vec![throughput("a", 80.0, &quiet)]),
+            MIN,
+        );
+        assert_eq!(c[0].threshold, MIN);
+        assert_eq!(c[0].verdict, Verdict::Regressed);
+    }
+
+    #[test]
+    fn points_measured_once_have_no_noise() {
+        // Only the noise of the repeated run counts.
+        let c = compare(
+            &results(vec![throughput("a", 100.0, &[100.0])]),
+            &results(vec![throughput("a", 94.0, &[90.0, 100.0, 110.0])]),
+            MIN,
+        );
+        assert!((c[0].threshold - SIGMAS * 0.1).abs() < 1e-9);
+        assert!(!c[0].repeated);
+
+        let c = compare(
+            &results(vec![throughput("a", 100.0, &[])]),
+            &results(vec![throughput("a", 94.0, &[])]),
+            MIN,
+        );
+        assert_eq!(c[0].threshold, MIN);
+        assert_eq!(c[0].verdict, Verdict::Regressed);
+    }
+
+    #[test]
+    fn rate_sweeps_and_other_metrics_are_not_compared() {
+        let mut rate = latency("a", 100);
+        rate.axis = Axis::Rate;
+        let mut other = throughput("b", 100.0, &[]);
+        other.metric = Metric::Other;
+        assert!(compare(&results(vec![rate, other]), &results(vec![]), MIN).is_empty());
+    }
+}
@@ -0,0 +1,233 @@
+//! One-to-one communications moving messages in batches instead of one by one.
+//!
+//! Receivers block for the first message and then drain whatever is queued,
+//! up to `BATCH` messages, with the non-blocking API of each channel.
+//! Messages left in the channel are received by the next batch.
+//! Senders either send message by message, or send `Vec`s of `BATCH` messages.
+
+use crate::std_thread::Tx;
+use std::{
+    sync::{Arc, Barrier},
+    thread::JoinHandle,
+};
+
+pub const BATCH: usize = 64;
+
+/// Send a batch of messages at once.
+pub type TxBatch = Box<dyn Fn(Vec<usize>) + Send>;
+
+/// Append at least one message to the buffer.
+pub type RxBatch = Box<dyn Fn(&mut Vec<usize>) + Send>;
+
+pub struct OneToOneBatch {
+    handler: Vec<JoinHandle<usize>>,
+    barrier: Arc<Barrier>,
+}
+
+impl OneToOneBatch {
+    /// Senders send message by message, and receivers drain in batches.
+    pub fn new(n: usize, count: usize, mkch: fn() -> (Tx, RxBatch)) -> Self {
+        let mut b = Self::with_barrier(n);
+
+        for _ in 0..n {
+            let (tx, rx) = mkch();
+
+            // Create a sender.
+            let bar = b.barrier.clone();
+            let th = std::thread::spawn(move || {
+                bar.wait();
+                for _ in 0..count {
+                    tx(1);
+                }
+                0
+            });
+            b.handler.push(th);
+
+            b.spawn_receiver(count, rx);
+        }
+
+        b
+    }
+
+    /// Senders send `Vec`s of `BATCH` messages, and receivers drain in batches.
+    pub fn new_batch_send(n: usize, count: usize, mkch: fn() -> (TxBatch, RxBatch)) -> Self {
+        let mut b = Self::with_barrier(n);
+
+        for _ in 0..n {
+            let (tx, rx) = mkch();
+
+            // Create a sender.
+            let bar = b.barrier.clone();
+            let th = std::thread::spawn(move || {
+                bar.wait();
+                let mut rest = count;
+                while rest > 0 {
+                    let len = rest.min(BATCH);
+                    tx(vec![1; len]);
+                    rest -= len;
+                }
+                0
+            });
+            b.handler.push(th);
+
+            b.spawn_receiver(count, rx);
+        }
+
+        b
+    }
+
+    fn with_barrier(n: usize) -> Self {
+        Self {
+            handler: Vec::new(),
+            barrier: Arc::new(Barrier::new(n * 2 + 1)),
+        }
+    }
+
+    fn spawn_receiver(&mut self, count: usize, rx: RxBatch) {
+        let bar = self.barrier.clone();
+        let th = std::thread::spawn(move || {
+            bar.wait();
+            let mut buf = Vec::with_capacity(BATCH);
+            let mut received = 0;
+            let mut cnt = 0;
+            while received < count {
+                buf.clear();
+                rx(&mut buf);
+                received += buf.len();
+                cnt += buf.iter().sum::<usize>();
+            }
+            cnt
+        });
+        self.handler.push(th);
+    }
+
+    pub fn start(&mut self) {
+        self.barrier.wait();
+        let v = std::mem::take(&mut self.handler);
+        for th in v {
+            th.join().unwrap();
+        }
+    }
+}
+
+/// `std::sync::mpsc::sync_channel` drained with `try_iter`.
+pub fn new_one_to_one_sync_channel(n: usize, count: usize) -> OneToOneBatch {
+    fn mkch() -> (Tx, RxBatch) {
+        let (tx, rx) = std::sync::mpsc::sync_channel(1024);
+        (
+            Box::new(move |x| tx.send(x).unwrap()),
+            Box::new(move |buf| {
+                buf.push(rx.recv().unwrap());
+                buf.extend(rx.try_iter().take(BATCH - 1));
+            }),
+        )
+    }
+
+    OneToOneBatch::new(n, count, mkch)
+}
+
+/// `flume::bounded` drained with `drain`,
+/// which takes every queued message at once, so it cannot be limited to `BATCH`.
+pub fn new_one_to_one_flume(n: usize, count: usize) -> OneToOneBatch {
+    fn mkch() -> (Tx, RxBatch) {
+        let (tx, rx) = flume::bounded(1024);
+        (
+            Box::new(move |x| tx.send(x).unwrap()),
+            Box::new(move |buf| {
+                buf.push(rx.recv().unwrap());
+                buf.extend(rx.drain());
+            }),
+        )
+    }
+
+    OneToOneBatch::new(n, count, mkch)
+}
+
+/// `crossbeam::channel::bounded` drained with `try_iter`.
+pub fn new_one_to_one_crossbeam(n: usize, count: usize) -> OneToOneBatch {
+    fn mkch() -> (Tx, RxBatch) {
+        let (tx, rx) = crossbeam::channel::bounded(1024);
+        (
+            Box::new(move |x| tx.send(x).unwrap()),
+            Box::new(move |buf| {
+                buf.push(rx.recv().unwrap());
+                buf.extend(rx.try_iter().take(BATCH - 1));
+            }),
+        )
+    }
+
+    OneToOneBatch::new(n, count, mkch)
+}
+
+/// `kanal::bounded` drained with a `try_recv` loop.
+pub fn new_one_to_one_kanal(n: usize, count: usize) -> OneToOneBatch {
+    fn mkch() -> (Tx, RxBatch) {
+        let (tx, rx) = kanal::bounded(1024);
+        (
+            Box::new(move |x| tx.send(x).unwrap()),
+            Box::new(move |buf| {
+                buf.push(rx.recv().unwrap());
+                // `try_recv` fails once the sender is gone and the channel is empty.
+                while buf.len() < BATCH {
+                    match rx.try_recv() {
+                        Ok(Some(x)) => buf.push(x),
+                        _ => break,
+                    }
+                }
+            }),
+        )
+    }
+
+    OneToOneBatch::new(n, count, mkch)
+}
+
+// Channels of `Vec`s hold 1024 / BATCH batches,
+// so that as many messages as the other bounded channels can be queued.
+
+pub fn new_batch_send_sync_channel(n: usize, count: usize) -> OneToOneBatch {
+    fn mkch() -> (TxBatch, RxBatch) {
+        let (tx, rx) = std::sync::mpsc::sync_channel(1024 / BATCH);
+        (
+            Box::new(move |x| tx.send(x).unwrap()),
+            Box::new(move |buf| buf.extend(rx.recv().unwrap())),
+        )
+    }
+
+    OneToOneBatch::new_batch_send(n, count, mkch)
+}
+
+pub fn new_batch_send_flume(n: usize, count: usize) -> OneToOneBatch {
+    fn mkch() -> (TxBatch, RxBatch) {
+        let (tx, rx) = flume::bounded(1024 / BATCH);
+        (
+            Box::new(move |x| tx.send(x).unwrap()),
+            Box::new(move |buf| buf.extend(rx.recv().unwrap())),
+        )
+    }
+
+    OneToOneBatch::new_batch_send(n, count, mkch)
+}
+
+pub fn new_batch_send_crossbeam(n: usize, count: usize) -> OneToOneBatch {
+    fn mkch() -> (TxBatch, RxBatch) {
+        let (tx, rx) = crossbeam::channel::bounded(1024 / BATCH);
+        (
+            Box::new(move |x| tx.send(x).unwrap()),
+            Box::new(move |buf| buf.extend(rx.recv().unwrap())),
+        )
+    }
+
+    OneToOneBatch::new_batch_send(n, count, mkch)
+}
+
+pub fn new_batch_send_kanal(n: usize, count: usize) -> OneToOneBatch {
+    fn mkch() -> (TxBatch, RxBatch) {
+        let (tx, rx) = kanal::bounded(1024 / BATCH);
+        (
+            Box::new(move |x| tx.send(x).unwrap()),
+            Box::new(move |buf| buf.extend(rx.recv().unwrap())),
+        )
+    }
+
+    OneToOneBatch::new_batch_send(n, count, mkch)
+}
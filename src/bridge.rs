@@ -0,0 +1,300 @@
+//! One-to-one communications crossing the boundary between a tokio task and
+//! a plain `std::thread`.
+//!
+//! Senders either saturate the channel to measure throughput,
+//! or are paced to measure latency.
+//! Latency of a saturated channel would be the time messages wait in a full queue.
+
+use crate::{latency::Latency, workload};
+use async_barrier::Barrier;
+use std::{
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::{runtime::Handle, sync::mpsc};
+
+/// Messages per second of each paced sender, well below what any route sustains.
+pub const PACED_RATE: usize = 10_000;
+
+/// A paced sender sleeps for a tick, and then sends the messages due in it back to back.
+/// Sleeping for each message would take a shorter interval than the resolution of tokio's timer,
+/// and spinning instead would make many senders contend for CPUs.
+const TICK: Duration = workload::MIN_GAP;
+
+/// How senders send.
+#[derive(Debug, Clone, Copy)]
+pub enum Pace {
+    /// As fast as possible, to measure throughput. Latency is not measured.
+    Saturate,
+    /// Messages per second, sent in batches every `TICK`.
+    /// Every message carries the time it was sent,
+    /// so its latency is the time to cross the channel and wake the receiver
+    /// rather than how late the timer woke the sender.
+    Rate(usize),
+}
+
+impl Pace {
+    /// When the i-th message of a sender is due, if it is the first of a tick.
+    fn tick(self, start: Instant, i: usize) -> Option<Instant> {
+        let Pace::Rate(rate) = self else {
+            return None;
+        };
+        let batch = ((rate as f64 * TICK.as_secs_f64()) as usize).max(1);
+        i.is_multiple_of(batch)
+            .then(|| start + TICK.mul_f64((i / batch) as f64))
+    }
+
+    /// Sleep the thread until the i-th message is due, and return the time to send with it.
+    fn wait(self, start: Instant, i: usize) -> Instant {
+        if let Some(due) = self.tick(start, i) {
+            let now = Instant::now();
+            if due > now {
+                std::thread::sleep(due - now);
+            }
+        }
+        Instant::now()
+    }
+
+    /// Sleep the task until the i-th message is due, and return the time to send with it.
+    async fn wait_async(self, start: Instant, i: usize) -> Instant {
+        if let Some(due) = self.tick(start, i) {
+            tokio::time::sleep_until(due.into()).await;
+        }
+        Instant::now()
+    }
+
+    /// Buffer for the latency of count messages.
+    fn samples(self, count: usize) -> Vec<Duration> {
+        match self {
+            Pace::Saturate => Vec::new(),
+            Pace::Rate(_) => Vec::with_capacity(count),
+        }
+    }
+
+    fn record(self, samples: &mut Vec<Duration>, sent: Instant) {
+        if let Pace::Rate(_) = self {
+            samples.push(sent.elapsed());
+        }
+    }
+}
+
+pub struct Bridge {
+    threads: Vec<std::thread::JoinHandle<Vec<Duration>>>,
+    tasks: Vec<tokio::task::JoinHandle<Vec<Duration>>>,
+    barrier: Arc<Barrier>,
+    runtime: Handle,
+    pace: Pace,
+}
+
+impl Bridge {
+    fn new(runtime: &Handle, n: usize, pace: Pace) -> Self {
+        Self {
+            threads: Vec::new(),
+            tasks: Vec::new(),
+            barrier: Arc::new(Barrier::new(2 * n + 1)),
+            runtime: runtime.clone(),
+            pace,
+        }
+    }
+
+    fn spawn_thread<F>(&mut self, f: F)
+    where
+        F: FnOnce() -> Vec<Duration> + Send + 'static,
+    {
+        let bar = self.barrier.clone();
+        let th = std::thread::spawn(move || {
+            futures::executor::block_on(bar.wait());
+            f()
+        });
+        self.threads.push(th);
+    }
+
+    fn spawn_task<F>(&mut self, f: F)
+    where
+        F: Future<Output = Vec<Duration>> + Send + 'static,
+    {
+        let bar = self.barrier.clone();
+        let th = self.runtime.spawn(async move {
+            bar.wait().await;
+            f.await
+        });
+        self.tasks.push(th);
+    }
+
+    /// Run all the pairs and return the latency of the messages if senders are paced.
+    pub fn start(&mut self) -> Option<Latency> {
+        futures::executor::block_on(self.barrier.wait());
+
+        let mut v = Vec::new();
+        for th in std::mem::take(&mut self.threads) {
+            v.extend(th.join().unwrap());
+        }
+        for th in std::mem::take(&mut self.tasks) {
+            v.extend(self.runtime.block_on(th).unwrap());
+        }
+
+        match self.pace {
+            Pace::Saturate => None,
+            Pace::Rate(_) => Some(Latency::from_samples(v)),
+        }
+    }
+}
+
+/// tokio task --(`flume::bounded`, `send_async`)--> `std::thread`
+pub fn new_tokio_to_thread_flume(runtime: &Handle, n: usize, count: usize, pace: Pace) -> Bridge {
+    let mut b = Bridge::new(runtime, n, pace);
+
+    for _ in 0..n {
+        let (tx, rx) = flume::bounded(1024);
+
+        // Create a sender.
+        b.spawn_task(async move {
+            let start = Instant::now();
+            for i in 0..count {
+                tx.send_async(pace.wait_async(start, i).await)
+                    .await
+                    .unwrap();
+            }
+            Vec::new()
+        });
+
+        // Create a receiver.
+        b.spawn_thread(move || {
+            let mut v = pace.samples(count);
+            for _ in 0..count {
+                pace.record(&mut v, rx.recv().unwrap());
+            }
+            v
+        });
+    }
+
+    b
+}
+
+/// tokio task --(`crossbeam::channel::unbounded`)--> `std::thread`
+///
+/// The sender yields periodically as in `tokio_bench`,
+/// because sending to an unbounded channel never awaits.
+pub fn new_tokio_to_thread_crossbeam(
+    runtime: &Handle,
+    n: usize,
+    count: usize,
+    pace: Pace,
+) -> Bridge {
+    let mut b = Bridge::new(runtime, n, pace);
+
+    for _ in 0..n {
+        let (tx, rx) = crossbeam::channel::unbounded();
+
+        // Create a sender.
+        b.spawn_task(async move {
+            let start = Instant::now();
+            for n in 0..count {
+                tx.send(pace.wait_async(start, n).await).unwrap();
+                if n & 0xff == 0 {
+                    tokio::task::yield_now().await;
+                }
+            }
+            Vec::new()
+        });
+
+        // Create a receiver.
+        b.spawn_thread(move || {
+            let mut v = pace.samples(count);
+            for _ in 0..count {
+                pace.record(&mut v, rx.recv().unwrap());
+            }
+            v
+        });
+    }
+
+    b
+}
+
+/// tokio task --(`tokio::sync::mpsc::channel`, `blocking_recv`)--> `std::thread`
+pub fn new_tokio_to_thread_tokio(runtime: &Handle, n: usize, count: usize, pace: Pace) -> Bridge {
+    let mut b = Bridge::new(runtime, n, pace);
+
+    for _ in 0..n {
+        let (tx, mut rx) = mpsc::channel(1024);
+
+        // Create a sender.
+        b.spawn_task(async move {
+            let start = Instant::now();
+            for i in 0..count {
+                tx.send(pace.wait_async(start, i).await).await.unwrap();
+            }
+            Vec::new()
+        });
+
+        // Create a receiver.
+        b.spawn_thread(move || {
+            let mut v = pace.samples(count);
+            for _ in 0..count {
+                pace.record(&mut v, rx.blocking_recv().unwrap());
+            }
+            v
+        });
+    }
+
+    b
+}
+
+/// `std::thread` --(`tokio::sync::mpsc::channel`, `blocking_send`)--> tokio task
+pub fn new_thread_to_tokio_tokio(runtime: &Handle, n: usize, count: usize, pace: Pace) -> Bridge {
+    let mut b = Bridge::new(runtime, n, pace);
+
+    for _ in 0..n {
+        let (tx, mut rx) = mpsc::channel(1024);
+
+        // Create a sender.
+        b.spawn_thread(move || {
+            let start = Instant::now();
+            for i in 0..count {
+                tx.blocking_send(pace.wait(start, i)).unwrap();
+            }
+            Vec::new()
+        });
+
+        // Create a receiver.
+        b.spawn_task(async move {
+            let mut v = pace.samples(count);
+            for _ in 0..count {
+                pace.record(&mut v, rx.recv().await.unwrap());
+            }
+            v
+        });
+    }
+
+    b
+}
+
+/// `std::thread` --(`flume::bounded`, `recv_async`)--> tokio task
+pub fn new_thread_to_tokio_flume(runtime: &Handle, n: usize, count: usize, pace: Pace) -> Bridge {
+    let mut b = Bridge::new(runtime, n, pace);
+
+    for _ in 0..n {
+        let (tx, rx) = flume::bounded(1024);
+
+        // Create a sender.
+        b.spawn_thread(move || {
+            let start = Instant::now();
+            for i in 0..count {
+                tx.send(pace.wait(start, i)).unwrap();
+            }
+            Vec::new()
+        });
+
+        // Create a receiver.
+        b.spawn_task(async move {
+            let mut v = pace.samples(count);
+            for _ in 0..count {
+                pace.record(&mut v, rx.recv_async().await.unwrap());
+            }
+            v
+        });
+    }
+
+    b
+}
@@ -0,0 +1,101 @@
+//! A global allocator counting allocations, for the `alloc-count` feature.
+//!
+//! Counting adds atomic operations to every allocation,
+//! so throughput should not be compared between builds with and without the feature.
+
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "alloc-count")]
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static BYTES: AtomicUsize = AtomicUsize::new(0);
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// Wraps `System`, counting allocations and live bytes.
+///
+/// Install it with `#[global_allocator]` to make `Region` report anything.
+#[cfg(feature = "alloc-count")]
+pub struct CountingAlloc;
+
+#[cfg(feature = "alloc-count")]
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let p = System.alloc(layout);
+        if !p.is_null() {
+            grow(layout.size());
+        }
+        p
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let p = System.alloc_zeroed(layout);
+        if !p.is_null() {
+            grow(layout.size());
+        }
+        p
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let p = System.realloc(ptr, layout, new_size);
+        if !p.is_null() {
+            CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+            grow(new_size);
+        }
+        p
+    }
+}
+
+#[cfg(feature = "alloc-count")]
+fn grow(size: usize) {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    BYTES.fetch_add(size, Ordering::Relaxed);
+    let current = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK.fetch_max(current, Ordering::Relaxed);
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct AllocStats {
+    /// Number of allocations, including reallocations.
+    pub allocations: usize,
+    /// Total bytes allocated.
+    pub bytes: usize,
+    /// Peak of bytes alive on the heap, above those alive when the region started.
+    pub peak_bytes: usize,
+}
+
+/// Allocations during a region of the program.
+///
+/// Regions must not overlap, because the peak is shared by the whole process.
+pub struct Region {
+    allocations: usize,
+    bytes: usize,
+    /// Bytes alive when the region started, from which the peak is restarted.
+    current: usize,
+}
+
+impl Region {
+    pub fn start() -> Self {
+        let current = CURRENT.load(Ordering::Relaxed);
+        PEAK.store(current, Ordering::Relaxed);
+        Region {
+            allocations: ALLOCATIONS.load(Ordering::Relaxed),
+            bytes: BYTES.load(Ordering::Relaxed),
+            current,
+        }
+    }
+
+    pub fn stop(&self) -> AllocStats {
+        AllocStats {
+            allocations: ALLOCATIONS.load(Ordering::Relaxed) - self.allocations,
+            bytes: BYTES.load(Ordering::Relaxed) - self.bytes,
+            peak_bytes: PEAK.load(Ordering::Relaxed).saturating_sub(self.current),
+        }
+    }
+}
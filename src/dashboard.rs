@@ -0,0 +1,115 @@
+//! A static HTML dashboard of one or more result sets, e.g., of several machines or versions.
+//!
+//! The page is a single file with the results embedded as JSON, and its script and style inline,
+//! so it works offline and can be served as is from `docs/`.
+//! It is written next to the criterion reports in `docs/` rather than over their index.
+
+use crate::{
+    environment::Environment,
+    plot::PALETTE,
+    results::{Metric, ResultSet},
+};
+use serde::Serialize;
+use std::{fs, io, path::Path};
+
+pub const DEFAULT_OUTPUT: &str = "docs/dashboard.html";
+
+const TEMPLATE: &str = include_str!("dashboard.html");
+
+#[derive(Serialize)]
+struct Run<'a> {
+    label: &'a str,
+    timestamp: u64,
+    environment: &'a Environment,
+    count: usize,
+    scenarios: Vec<Scenario<'a>>,
+    points: Vec<Point<'a>>,
+}
+
+#[derive(Serialize)]
+struct Scenario<'a> {
+    name: &'a str,
+    /// What n counts, as the label of the x axis.
+    axis: &'static str,
+    metric: Metric,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    count: Option<usize>,
+}
+
+/// Throughput of a record, or latency if the scenario measures latency.
+/// Scenarios with metrics of their own are listed but not charted.
+#[derive(Serialize)]
+struct Point<'a> {
+    scenario: &'a str,
+    backend: &'a str,
+    n: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ops: Option<f64>,
+    /// Latency in seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    p50: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    p99: Option<f64>,
+    /// Of ops/s, or of p99 latency.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stddev: Option<f64>,
+}
+
+/// Render result sets, as (label, results), into an HTML document.
+pub fn render(runs: &[(String, ResultSet)]) -> String {
+    let runs: Vec<Run> = runs
+        .iter()
+        .map(|(label, results)| Run {
+            label,
+            timestamp: results.timestamp,
+            environment: &results.environment,
+            count: results.count,
+            scenarios: results
+                .scenarios()
+                .into_iter()
+                .map(|(name, _)| Scenario {
+                    name,
+                    axis: results.axis(name).label(),
+                    metric: results.metric(name),
+                    count: results.count_of(name),
+                })
+                .collect(),
+            points: results
+                .records
+                .iter()
+                .filter_map(|r| {
+                    let (ops, latency) = match r.metric {
+                        Metric::Throughput => (Some(r.ops_per_sec), None),
+                        Metric::Latency => (None, Some(r.latency?)),
+                        Metric::Other => return None,
+                    };
+                    Some(Point {
+                        scenario: &r.scenario,
+                        backend: &r.backend,
+                        n: r.n,
+                        ops,
+                        p50: latency.map(|l| l.p50.as_secs_f64()),
+                        p99: latency.map(|l| l.p99.as_secs_f64()),
+                        stddev: r.stddev(),
+                    })
+                })
+                .collect(),
+        })
+        .collect();
+
+    // "</" would end the script element the data is embedded in.
+    let data = serde_json::to_string(&runs).unwrap().replace("</", "<\\/");
+    let palette = serde_json::to_string(PALETTE).unwrap();
+    TEMPLATE
+        .replace("/*DATA*/", &data)
+        .replace("/*PALETTE*/", &palette)
+}
+
+/// Write the dashboard of result sets, as (label, results), to `output`.
+pub fn write<P: AsRef<Path>>(runs: &[(String, ResultSet)], output: P) -> io::Result<()> {
+    let output = output.as_ref();
+    if let Some(dir) = output.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(output, render(runs))
+}
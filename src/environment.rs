@@ -0,0 +1,117 @@
+//! The machine and the build a result set was measured on.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt, fs,
+};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Cache {
+    pub level: u32,
+    pub kind: String,
+    pub size: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Environment {
+    pub cpu_model: String,
+    /// Number of physical cores.
+    pub cores: usize,
+    /// Number of hardware threads.
+    pub threads: usize,
+    pub caches: Vec<Cache>,
+    pub kernel: String,
+    pub governor: Option<String>,
+    pub rustc: String,
+    pub profile: String,
+    pub dependencies: BTreeMap<String, String>,
+}
+
+impl Environment {
+    /// Capture the environment from `/proc` and sysfs, and the build information
+    /// embedded by `build.rs`.
+    /// Anything unavailable on this platform is left empty.
+    pub fn capture() -> Self {
+        let cpuinfo = fs::read_to_string("/proc/cpuinfo").unwrap_or_default();
+        let (cpu_model, cores) = parse_cpuinfo(&cpuinfo);
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+
+        let dependencies = env!("ASYNC_BENCH_DEPENDENCIES")
+            .split(',')
+            .filter_map(|dep| dep.split_once('='))
+            .map(|(name, version)| (name.to_string(), version.to_string()))
+            .collect();
+
+        Environment {
+            cpu_model,
+            cores: if cores == 0 { threads } else { cores },
+            threads,
+            caches: read_caches(),
+            kernel: read_trimmed("/proc/sys/kernel/osrelease").unwrap_or_default(),
+            governor: read_trimmed("/sys/devices/system/cpu/cpu0/cpufreq/scaling_governor"),
+            rustc: env!("ASYNC_BENCH_RUSTC").to_string(),
+            profile: env!("ASYNC_BENCH_PROFILE").to_string(),
+            dependencies,
+        }
+    }
+}
+
+impl fmt::Display for Environment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "CPU: {}", self.cpu_model)?;
+        writeln!(f, "Cores: {} cores, {} threads", self.cores, self.threads)?;
+        for c in self.caches.iter() {
+            writeln!(f, "L{} cache ({}): {}", c.level, c.kind, c.size)?;
+        }
+        writeln!(f, "Kernel: {}", self.kernel)?;
+        if let Some(governor) = &self.governor {
+            writeln!(f, "Governor: {governor}")?;
+        }
+        write!(f, "Rust: {} ({})", self.rustc, self.profile)
+    }
+}
+
+fn read_trimmed(path: &str) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+/// The model name and the number of distinct (physical id, core id) pairs.
+fn parse_cpuinfo(cpuinfo: &str) -> (String, usize) {
+    let mut model = String::new();
+    let mut cores = BTreeSet::new();
+    let mut physical_id = "";
+
+    for line in cpuinfo.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "model name" if model.is_empty() => model = value.to_string(),
+            "physical id" => physical_id = value,
+            "core id" => {
+                cores.insert((physical_id, value));
+            }
+            _ => (),
+        }
+    }
+
+    (model, cores.len())
+}
+
+fn read_caches() -> Vec<Cache> {
+    let mut v = Vec::new();
+    for i in 0.. {
+        let dir = format!("/sys/devices/system/cpu/cpu0/cache/index{i}");
+        let Some(level) = read_trimmed(&format!("{dir}/level")) else {
+            break;
+        };
+        v.push(Cache {
+            level: level.parse().unwrap_or(0),
+            kind: read_trimmed(&format!("{dir}/type")).unwrap_or_default(),
+            size: read_trimmed(&format!("{dir}/size")).unwrap_or_default(),
+        });
+    }
+    v
+}
@@ -0,0 +1,200 @@
+use async_barrier::Barrier;
+use futures::{
+    channel::mpsc,
+    future::{FutureObj, RemoteHandle},
+    lock::Mutex,
+    task::{Spawn, SpawnError, SpawnExt},
+    Sink, SinkExt, Stream, StreamExt,
+};
+use std::{fmt::Debug, sync::Arc};
+
+/// Spawns futures onto a tokio runtime, so that the runtime-agnostic
+/// scenarios of this module can run on tokio as well as on
+/// `futures::executor::ThreadPool`.
+#[derive(Clone)]
+pub struct TokioSpawner(pub tokio::runtime::Handle);
+
+impl Spawn for TokioSpawner {
+    fn spawn_obj(&self, future: FutureObj<'static, ()>) -> Result<(), SpawnError> {
+        self.0.spawn(future);
+        Ok(())
+    }
+}
+
+pub struct OneToOneFutures {
+    handler: Vec<RemoteHandle<usize>>,
+    barrier: Arc<Barrier>,
+}
+
+impl OneToOneFutures {
+    pub async fn start(&mut self) {
+        self.barrier.wait().await;
+        let v = std::mem::take(&mut self.handler);
+        for th in v {
+            th.await;
+        }
+    }
+
+    pub fn new<S, Tx, Rx>(sp: &S, n: usize, count: usize, f: fn() -> (Tx, Rx)) -> Self
+    where
+        S: Spawn,
+        Tx: Sink<usize> + Unpin + Send + 'static,
+        Tx::Error: Debug,
+        Rx: Stream<Item = usize> + Unpin + Send + 'static,
+    {
+        let mut v = Vec::new();
+        let barrier = async_barrier::Barrier::new(2 * n + 1);
+        let barrier = Arc::new(barrier);
+
+        for _ in 0..n {
+            let (mut tx, mut rx) = f();
+
+            // Create a sender.
+            let bar = barrier.clone();
+            let th = sp
+                .spawn_with_handle(async move {
+                    bar.wait().await;
+                    for _ in 0..count {
+                        tx.send(1).await.unwrap();
+                    }
+                    0
+                })
+                .unwrap();
+            v.push(th);
+
+            // Create a receiver.
+            let bar = barrier.clone();
+            let th = sp
+                .spawn_with_handle(async move {
+                    bar.wait().await;
+                    let mut cnt = 0;
+                    for _ in 0..count {
+                        let n = rx.next().await.unwrap();
+                        cnt += n;
+                    }
+                    cnt
+                })
+                .unwrap();
+            v.push(th);
+        }
+
+        Self {
+            handler: v,
+            barrier,
+        }
+    }
+}
+
+pub async fn new_one_to_one_unbounded<S: Spawn>(sp: &S, n: usize, count: usize) -> OneToOneFutures {
+    OneToOneFutures::new(sp, n, count, mpsc::unbounded)
+}
+
+pub async fn new_one_to_one_bounded<S: Spawn>(sp: &S, n: usize, count: usize) -> OneToOneFutures {
+    fn mkch() -> (mpsc::Sender<usize>, mpsc::Receiver<usize>) {
+        mpsc::channel(1024)
+    }
+    OneToOneFutures::new(sp, n, count, mkch)
+}
+
+pub struct ManyToOneFutures {
+    handler: Vec<RemoteHandle<usize>>,
+    barrier: Arc<Barrier>,
+}
+
+impl ManyToOneFutures {
+    pub async fn start(&mut self) {
+        self.barrier.wait().await;
+        let v = std::mem::take(&mut self.handler);
+        for th in v {
+            th.await;
+        }
+    }
+
+    pub fn new_bounded<S: Spawn>(sp: &S, n: usize, count: usize) -> Self {
+        let mut v = Vec::new();
+        let barrier = async_barrier::Barrier::new(n + 2);
+        let barrier = Arc::new(barrier);
+        let (tx, mut rx) = mpsc::channel(1024);
+
+        // Create a receiver.
+        let bar = barrier.clone();
+        let th = sp
+            .spawn_with_handle(async move {
+                bar.wait().await;
+                let mut cnt = 0;
+                for _ in 0..(count * n) {
+                    let n = rx.next().await.unwrap();
+                    cnt += n;
+                }
+                cnt
+            })
+            .unwrap();
+        v.push(th);
+
+        for _ in 0..n {
+            // Create a sender.
+            let bar = barrier.clone();
+            let mut ch = tx.clone();
+            let th = sp
+                .spawn_with_handle(async move {
+                    bar.wait().await;
+                    for _ in 0..count {
+                        ch.send(1).await.unwrap();
+                    }
+                    0
+                })
+                .unwrap();
+            v.push(th);
+        }
+
+        Self {
+            handler: v,
+            barrier,
+        }
+    }
+}
+
+pub async fn new_many_to_one_bounded<S: Spawn>(sp: &S, n: usize, count: usize) -> ManyToOneFutures {
+    ManyToOneFutures::new_bounded(sp, n, count)
+}
+
+pub struct MutexBench {
+    handler: Vec<RemoteHandle<()>>,
+    barrier: Arc<Barrier>,
+}
+
+impl MutexBench {
+    pub fn new<S: Spawn>(sp: &S, n: usize, count: usize) -> Self {
+        let mut v = Vec::new();
+        let barrier = Arc::new(Barrier::new(n + 1));
+        let shared = Arc::new(Mutex::new(0));
+
+        for _ in 0..n {
+            let bar = barrier.clone();
+            let n = shared.clone();
+            let th = sp
+                .spawn_with_handle(async move {
+                    bar.wait().await;
+                    for _ in 0..count {
+                        let mut guard = n.lock().await;
+                        *guard += 1;
+                    }
+                })
+                .unwrap();
+            v.push(th);
+        }
+
+        Self {
+            handler: v,
+            barrier,
+        }
+    }
+
+    pub async fn start(&mut self) {
+        self.barrier.wait().await;
+        let v = std::mem::take(&mut self.handler);
+        for th in v {
+            th.await;
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use crate::affinity::Topology;
+use crossbeam::channel::Sender;
+use glommio::{
+    channels::{local_channel, shared_channel},
+    ExecutorJoinHandle, GlommioError, LocalExecutorBuilder,
+};
+use std::{
+    io,
+    sync::{Arc, Barrier, Condvar, Mutex},
+};
+
+/// Where the sender and the receiver of each pair are placed.
+#[derive(Debug, Clone, Copy)]
+pub enum Placement {
+    /// Both tasks run on one executor pinned to a single core,
+    /// and talk through a `local_channel`.
+    SameCore,
+    /// The tasks run on two executors pinned to different cores,
+    /// and talk through a `shared_channel`.
+    CrossCore,
+}
+
+/// One-to-one communications on glommio, a thread-per-core runtime.
+///
+/// Every executor is pinned by `glommio::Placement::Fixed` to its own physical core
+/// among the CPUs this process may run on.
+/// The i-th pair uses core `i` for `SameCore`, and cores `2i` and `2i + 1` for `CrossCore`.
+pub struct OneToOneGlommio {
+    handler: Vec<ExecutorJoinHandle<usize>>,
+    barrier: Arc<Barrier>,
+}
+
+/// Lets spawned executors go on once all of them have been spawned,
+/// or makes them return if one of them could not be.
+#[derive(Clone, Default)]
+struct Gate(Arc<(Mutex<Option<bool>>, Condvar)>);
+
+impl Gate {
+    fn open(&self, go: bool) {
+        let (lock, cvar) = &*self.0;
+        *lock.lock().unwrap() = Some(go);
+        cvar.notify_all();
+    }
+
+    fn wait(&self) -> bool {
+        let (lock, cvar) = &*self.0;
+        let mut go = lock.lock().unwrap();
+        loop {
+            match *go {
+                Some(go) => return go,
+                None => go = cvar.wait(go).unwrap(),
+            }
+        }
+    }
+}
+
+type Spawned = Result<ExecutorJoinHandle<usize>, GlommioError<()>>;
+
+impl OneToOneGlommio {
+    /// Fails if there are not enough cores for n pairs, so that executors would share a core,
+    /// or if an executor cannot start, e.g., without io_uring or enough locked memory.
+    pub fn new(n: usize, count: usize, placement: Placement) -> io::Result<Self> {
+        let cores = Topology::detect()?.cores();
+        let executors = match placement {
+            Placement::SameCore => n,
+            Placement::CrossCore => 2 * n,
+        };
+        if cores.len() < executors {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "{executors} cores are needed, but {} are available",
+                    cores.len()
+                ),
+            ));
+        }
+
+        let barrier = Arc::new(Barrier::new(executors + 1));
+        let gate = Gate::default();
+        let (ready_tx, ready_rx) = crossbeam::channel::unbounded();
+        let mut spawned = Vec::new();
+        for i in 0..n {
+            let ex = Executor {
+                count,
+                barrier: barrier.clone(),
+                gate: gate.clone(),
+                ready: ready_tx.clone(),
+            };
+            match placement {
+                Placement::SameCore => spawned.push(ex.same_core(cores[i])),
+                Placement::CrossCore => {
+                    spawned.extend(ex.cross_core(cores[2 * i], cores[2 * i + 1]))
+                }
+            }
+        }
+
+        // An executor which started sends on `ready`, and one which failed to start drops it,
+        // so this returns once every executor has either started or failed.
+        drop(ready_tx);
+        let started = ready_rx.iter().count();
+
+        let mut v = Vec::new();
+        let mut error = None;
+        for th in spawned {
+            match th {
+                Ok(th) => v.push(th),
+                Err(e) => error = Some(e.to_string()),
+            }
+        }
+        if started == executors && error.is_none() {
+            gate.open(true);
+            return Ok(Self {
+                handler: v,
+                barrier,
+            });
+        }
+
+        // Executors which started return right away.
+        gate.open(false);
+        for th in v {
+            if let Err(e) = th.join() {
+                error.get_or_insert(e.to_string());
+            }
+        }
+        Err(io::Error::other(error.unwrap_or_else(|| {
+            "an executor failed to start".to_string()
+        })))
+    }
+
+    pub fn start(&mut self) {
+        self.barrier.wait();
+        let v = std::mem::take(&mut self.handler);
+        for th in v {
+            th.join().unwrap();
+        }
+    }
+}
+
+/// What every executor of a run shares.
+struct Executor {
+    count: usize,
+    barrier: Arc<Barrier>,
+    gate: Gate,
+    ready: Sender<()>,
+}
+
+impl Executor {
+    /// Report that this executor has started, and wait until all the others have.
+    /// Returns false if the run is given up.
+    fn started(self) -> (bool, Arc<Barrier>) {
+        self.ready.send(()).unwrap();
+        drop(self.ready);
+        (self.gate.wait(), self.barrier)
+    }
+
+    /// An executor running both the sender and the receiver of a pair.
+    fn same_core(self, core: usize) -> Spawned {
+        let count = self.count;
+        LocalExecutorBuilder::new(glommio::Placement::Fixed(core)).spawn(move || async move {
+            let (go, bar) = self.started();
+            if !go {
+                return 0;
+            }
+            let (tx, rx) = local_channel::new_bounded(1024);
+            bar.wait();
+
+            // Create a sender.
+            let sender = glommio::spawn_local(async move {
+                for _ in 0..count {
+                    tx.send(1).await.unwrap();
+                }
+            });
+
+            // Create a receiver.
+            let receiver = glommio::spawn_local(async move {
+                let mut cnt = 0;
+                for _ in 0..count {
+                    let n = rx.recv().await.unwrap();
+                    cnt += n;
+                }
+                cnt
+            });
+
+            sender.await;
+            receiver.await
+        })
+    }
+
+    /// Executors of the sender and of the receiver of a pair.
+    fn cross_core(self, sender_core: usize, receiver_core: usize) -> [Spawned; 2] {
+        let count = self.count;
+        let (tx, rx) = shared_channel::new_bounded(1024);
+        let other = Executor {
+            count,
+            barrier: self.barrier.clone(),
+            gate: self.gate.clone(),
+            ready: self.ready.clone(),
+        };
+
+        // Create a sender.
+        let sender = LocalExecutorBuilder::new(glommio::Placement::Fixed(sender_core)).spawn(
+            move || async move {
+                let (go, bar) = self.started();
+                if !go {
+                    return 0;
+                }
+                let tx = tx.connect().await;
+                bar.wait();
+                for _ in 0..count {
+                    tx.send(1).await.unwrap();
+                }
+                0
+            },
+        );
+
+        // Create a receiver.
+        let receiver = LocalExecutorBuilder::new(glommio::Placement::Fixed(receiver_core)).spawn(
+            move || async move {
+                let (go, bar) = other.started();
+                if !go {
+                    return 0;
+                }
+                let rx = rx.connect().await;
+                bar.wait();
+                let mut cnt = 0;
+                for _ in 0..count {
+                    let n = rx.recv().await.unwrap();
+                    cnt += n;
+                }
+                cnt
+            },
+        );
+
+        [sender, receiver]
+    }
+}
+
+pub fn new_one_to_one_same_core(n: usize, count: usize) -> io::Result<OneToOneGlommio> {
+    OneToOneGlommio::new(n, count, Placement::SameCore)
+}
+
+pub fn new_one_to_one_cross_core(n: usize, count: usize) -> io::Result<OneToOneGlommio> {
+    OneToOneGlommio::new(n, count, Placement::CrossCore)
+}
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+use std::{fmt, time::Duration};
+
+/// Summary of latency samples.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Latency {
+    #[serde(with = "nanos")]
+    pub mean: Duration,
+    #[serde(with = "nanos")]
+    pub p50: Duration,
+    #[serde(with = "nanos")]
+    pub p99: Duration,
+    #[serde(with = "nanos")]
+    pub p999: Duration,
+    #[serde(with = "nanos")]
+    pub max: Duration,
+}
+
+impl Latency {
+    pub fn from_samples(mut v: Vec<Duration>) -> Self {
+        if v.is_empty() {
+            return Self::default();
+        }
+
+        v.sort_unstable();
+        let percentile = |p: f64| v[((v.len() - 1) as f64 * p).round() as usize];
+        let sum: Duration = v.iter().sum();
+
+        Latency {
+            mean: sum / v.len() as u32,
+            p50: percentile(0.5),
+            p99: percentile(0.99),
+            p999: percentile(0.999),
+            max: v[v.len() - 1],
+        }
+    }
+}
+
+impl fmt::Display for Latency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "mean = {:>10.3?}, p50 = {:>10.3?}, p99 = {:>10.3?}, p99.9 = {:>10.3?}, max = {:>10.3?}",
+            self.mean, self.p50, self.p99, self.p999, self.max
+        )
+    }
+}
+
+/// (De)serialize `Duration` as nanoseconds, which is easier to handle
+/// outside of Rust than serde's default `{ secs, nanos }`.
+mod nanos {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(d: &Duration, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_u64(d.as_nanos() as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Duration, D::Error> {
+        u64::deserialize(d).map(Duration::from_nanos)
+    }
+}
@@ -1,7 +1,33 @@
-const MAX_COUNT: usize = 10000;
-
+#[cfg(target_os = "linux")]
+pub mod affinity;
 pub mod async_std_bench;
+pub mod backlog;
+pub mod baseline;
+pub mod batch;
+pub mod bridge;
+pub mod counting_alloc;
+pub mod dashboard;
+pub mod environment;
+pub mod futures_bench;
+#[cfg(target_os = "linux")]
+pub mod glommio_bench;
+pub mod latency;
+pub mod open_loop;
+pub mod plot;
+pub mod polling;
+pub mod registry;
+pub mod report;
+pub mod results;
+pub mod rusage;
+pub mod shutdown;
+pub mod smol_bench;
+pub mod spsc;
 pub mod std_thread;
 pub mod thread_crossbeam;
 pub mod thread_flume;
+pub mod thread_kanal;
+pub mod thread_ringbuf;
+pub mod thread_spsc;
+pub mod thread_thingbuf;
 pub mod tokio_bench;
+pub mod workload;
@@ -1,238 +1,558 @@
-use std::time::SystemTime;
-use tokio::runtime::Runtime as TokioRuntime;
+use latency::Latency;
+use registry::Runtimes;
+use results::{Axis, Metric, Record, ResultSet};
+use rusage::{Elapsed, Stopwatch};
+use std::time::Duration;
 
+#[cfg(target_os = "linux")]
+mod affinity;
 mod async_std_bench;
+mod backlog;
+mod baseline;
+mod batch;
+mod bridge;
+mod counting_alloc;
+mod dashboard;
+mod environment;
+mod futures_bench;
+#[cfg(target_os = "linux")]
+mod glommio_bench;
+mod latency;
+mod open_loop;
+mod plot;
+mod polling;
+mod registry;
+mod report;
+mod results;
+mod rusage;
+mod shutdown;
+mod smol_bench;
+mod spsc;
 mod std_thread;
 mod thread_crossbeam;
 mod thread_flume;
+mod thread_kanal;
+mod thread_ringbuf;
+mod thread_spsc;
+mod thread_thingbuf;
 mod tokio_bench;
+mod workload;
 
-const MAX_COUNT: usize = 1000000;
-const EVAL_RANGE: &[usize] = &[1, 4, 8, 12, 16, 20, 24];
+#[cfg(feature = "alloc-count")]
+#[global_allocator]
+static GLOBAL: counting_alloc::CountingAlloc = counting_alloc::CountingAlloc;
+
+/// Messages per sender of a run, unless `--count` is given.
+const DEFAULT_COUNT: usize = 1_000_000;
+
+const SHUTDOWN_REPEAT: usize = 1000;
+const MAX_RATE: usize = 100_000_000;
 
 fn main() {
-    println!("one-to-one");
-    println!("std::thread (std::sync::mpsc::channel)");
-    run_one_to_one(std_thread::new_one_to_one_channel);
-
-    println!("std::thread (std::sync::mpsc::sync_channel)");
-    run_one_to_one(std_thread::new_one_to_one_sync_channel);
-
-    println!("std::thread (flume::unbounded)");
-    run_one_to_one(thread_flume::new_one_to_one_unbounded);
-
-    println!("std::thread (flume::bounded)");
-    run_one_to_one(thread_flume::new_one_to_one_bounded);
-
-    println!("std::thread (crossbeam::channel::unbounded)");
-    run_one_to_one(thread_crossbeam::new_one_to_one_unbounded);
-
-    println!("std::thread (crossbeam::channel::bounded)");
-    run_one_to_one(thread_crossbeam::new_one_to_one_bounded);
-
-    println!("async_std (async_std::channel::unbounded)");
-    for i in EVAL_RANGE {
-        async_std::task::block_on(async {
-            let mut hdl = async_std_bench::new_one_to_one_unbounded(*i).await;
-            let start = SystemTime::now();
-            hdl.start().await;
-            let dur = start.elapsed().unwrap();
-            let sec = dur.as_secs_f64();
-            println!(
-                "n = {:>2}: {:>10} [ops/s]",
-                i,
-                (MAX_COUNT as f64 / sec) as usize
-            );
-        });
-    }
-
-    println!("async_std (async_std::channel::bounded)");
-    for i in EVAL_RANGE {
-        async_std::task::block_on(async {
-            let mut hdl = async_std_bench::new_one_to_one_bounded(*i).await;
-            let start = SystemTime::now();
-            hdl.start().await;
-            let dur = start.elapsed().unwrap();
-            let sec = dur.as_secs_f64();
-            println!(
-                "n = {:>2}: {:>10} [ops/s]",
-                i,
-                (MAX_COUNT as f64 / sec) as usize
-            );
-        });
-    }
-
-    let runtime = TokioRuntime::new().unwrap();
-
-    println!("tokio (tokio::sync::mpsc::unbounded_channel)");
-    for i in EVAL_RANGE {
-        runtime.block_on(async {
-            let mut hdl = tokio_bench::new_one_to_one_unbounded(*i).await;
-            let start = SystemTime::now();
-            hdl.start().await;
-            let dur = start.elapsed().unwrap();
-            let sec = dur.as_secs_f64();
-            println!(
-                "n = {:>2}: {:>10} [ops/s]",
-                i,
-                (MAX_COUNT as f64 / sec) as usize
-            );
-        });
-    }
-
-    println!("tokio (tokio::sync::mpsc::channel)");
-    for i in EVAL_RANGE {
-        runtime.block_on(async {
-            let mut hdl = tokio_bench::new_one_to_one_bounded(*i).await;
-            let start = SystemTime::now();
-            hdl.start().await;
-            let dur = start.elapsed().unwrap();
-            let sec = dur.as_secs_f64();
-            println!(
-                "n = {:>2}: {:>10} [ops/s]",
-                i,
-                (MAX_COUNT as f64 / sec) as usize
-            );
-        });
+    let mut output = None;
+    let mut count = DEFAULT_COUNT;
+    let mut samples = 1;
+    let mut save_baseline = None;
+    let mut baseline = None;
+    let mut threshold = baseline::DEFAULT_THRESHOLD;
+    let mut bursty = registry::BURSTY;
+    let mut command = None;
+    let mut inputs = Vec::new();
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--output" | "-o" => output = args.next(),
+            "--count" | "-n" => match args.next().and_then(|s| s.parse().ok()) {
+                Some(n) if n > 0 => count = n,
+                _ => usage("--count takes a positive number of messages"),
+            },
+            "--samples" => match args.next().and_then(|s| s.parse().ok()) {
+                Some(k) if k > 0 => samples = k,
+                _ => usage("--samples takes a positive number of repetitions"),
+            },
+            "--burst" => match (args.next().and_then(|s| s.parse().ok()), &mut bursty) {
+                (Some(b), workload::Workload::Bursty { burst, .. }) if b > 0 => *burst = b,
+                _ => usage("--burst takes a positive number of messages"),
+            },
+            "--gap" => match (args.next().and_then(|s| s.parse().ok()), &mut bursty) {
+                (Some(ms), workload::Workload::Bursty { gap, .. }) if ms > 0 => {
+                    *gap = Duration::from_millis(ms)
+                }
+                _ => usage("--gap takes a positive number of milliseconds"),
+            },
+            "--save-baseline" => save_baseline = Some(arg_value(&arg, args.next())),
+            "--baseline" => baseline = Some(arg_value(&arg, args.next())),
+            "--threshold" => match args.next().and_then(|s| s.parse::<f64>().ok()) {
+                Some(p) if p >= 0.0 => threshold = p / 100.0,
+                _ => usage("--threshold takes a percentage"),
+            },
+            _ if arg.starts_with('-') => usage(&format!("unknown argument: {arg}")),
+            _ if command.is_none() => command = Some(arg),
+            _ => inputs.push(arg),
+        }
     }
 
-    println!();
-    println!("many-to-one");
-    println!("std::thread (std::sync::mpsc::sync_channel)");
-
-    run_many_to_one(std_thread::new_many_to_one_sync_channel);
-
-    println!("std::thread (flume::bounded)");
-    run_many_to_one(thread_flume::new_many_to_one_bounded);
-
-    println!("std::thread (crossbeam::channel::bounded)");
-    run_many_to_one(thread_crossbeam::new_many_to_one_bounded);
-
-    println!("async_std (async_std::channel::bounded)");
-    for i in EVAL_RANGE[1..].iter() {
-        async_std::task::block_on(async {
-            let mut hdl = async_std_bench::new_many_to_one_bounded(*i).await;
-            let start = SystemTime::now();
-            hdl.start().await;
-            let dur = start.elapsed().unwrap();
-            let sec = dur.as_secs_f64();
-            println!(
-                "n = {:>2}: {:>10} [ops/s]",
-                i,
-                ((MAX_COUNT / 10 * *i) as f64 / sec) as usize
-            );
-        });
-    }
-
-    println!("tokio (tokio::sync::mpsc::channel)");
-    for i in EVAL_RANGE[1..].iter() {
-        runtime.block_on(async {
-            let mut hdl = tokio_bench::new_many_to_one_bounded(*i).await;
-            let start = SystemTime::now();
-            hdl.start().await;
-            let dur = start.elapsed().unwrap();
-            let sec = dur.as_secs_f64();
-            println!(
-                "n = {:>2}: {:>10} [ops/s]",
-                i,
-                ((MAX_COUNT / 10 * *i) as f64 / sec) as usize
-            );
-        });
+    match command.as_deref() {
+        None => (),
+        Some("compare") => {
+            let [input] = inputs.as_slice() else {
+                usage("compare takes a result file");
+            };
+            let Some(baseline) = baseline else {
+                usage("compare needs --baseline <name>");
+            };
+            let current = load(input);
+            let regressed = compare_to_baseline(&current, &baseline, threshold);
+            std::process::exit(if regressed { 1 } else { 0 });
+        }
+        Some("dashboard") => {
+            if inputs.is_empty() {
+                usage("dashboard takes one or more result files");
+            }
+            // Runs are labelled by their paths.
+            let runs: Vec<_> = inputs
+                .iter()
+                .map(|input| {
+                    let label = input.strip_suffix(".json").unwrap_or(input);
+                    (label.to_string(), load(input))
+                })
+                .collect();
+            let path = output.unwrap_or_else(|| dashboard::DEFAULT_OUTPUT.to_string());
+            if let Err(e) = dashboard::write(&runs, &path) {
+                cannot_write(&path, e);
+            }
+            println!("{path}");
+            return;
+        }
+        Some("plot") => {
+            let [input] = inputs.as_slice() else {
+                usage("plot takes a result file");
+            };
+            let results = load(input);
+            let dir = output.unwrap_or_else(|| "figs".to_string());
+            match plot::write_all(&results, &dir) {
+                Ok(paths) => paths.iter().for_each(|path| println!("{path}")),
+                Err(e) => cannot_write(&dir, e),
+            }
+            return;
+        }
+        Some("report") => {
+            let [input] = inputs.as_slice() else {
+                usage("report takes a result file");
+            };
+            let results = load(input);
+            let markdown = report::render(&results);
+            match output {
+                Some(path) => {
+                    if let Err(e) = std::fs::write(&path, markdown) {
+                        cannot_write(&path, e);
+                    }
+                }
+                None => print!("{markdown}"),
+            }
+            return;
+        }
+        Some(command) => usage(&format!("unknown command: {command}")),
+    }
+    if !inputs.is_empty() {
+        usage(&format!("unexpected argument: {}", inputs[0]));
+    }
+    if (save_baseline.is_some() || baseline.is_some()) && samples < 2 {
+        usage("--save-baseline and --baseline need --samples 2 or more to tell changes from noise");
+    }
+
+    let environment = environment::Environment::capture();
+    println!("{environment}");
+    println!("Count: {count} messages per sender, unless a scenario prints otherwise");
+    let mut rep = Reporter::new(ResultSet::new(environment, count));
+    rep.samples = samples;
+    let output = output.unwrap_or_else(|| format!("results/{}.json", rep.results.timestamp));
+    rep.output = Some(output.clone());
+    println!("Results are saved to {output} as they are measured");
+
+    let runtimes = Runtimes::new();
+    for scenario in registry::scenarios(bursty) {
+        run_registered(&mut rep, &runtimes, &scenario);
+    }
+
+    rep.scenario("open-loop (n = target rate [msgs/s])", Axis::Rate);
+    rep.backend("std::thread (std::sync::mpsc::sync_channel)");
+    run_open_loop(&mut rep, open_loop::new_open_loop_sync_channel);
+
+    rep.backend("std::thread (flume::bounded)");
+    run_open_loop(&mut rep, open_loop::new_open_loop_flume);
+
+    rep.backend("std::thread (crossbeam::channel::bounded)");
+    run_open_loop(&mut rep, open_loop::new_open_loop_crossbeam);
+
+    rep.backend("std::thread (kanal::bounded)");
+    run_open_loop(&mut rep, open_loop::new_open_loop_kanal);
+
+    rep.backend("std::thread (tokio::sync::mpsc::channel)");
+    run_open_loop(&mut rep, open_loop::new_open_loop_tokio);
+
+    for consumer in [
+        backlog::Consumer::Work(Duration::from_micros(1)),
+        backlog::Consumer::Pause {
+            every: (count / 10).max(1),
+            pause: Duration::from_millis(10),
+        },
+    ] {
+        rep.scenario(&format!("backlog ({consumer:?})"), Axis::Single);
+        rep.backend("std::thread (std::sync::mpsc::channel)");
+        run_backlog(&mut rep, backlog::new_backlog_channel, consumer);
+
+        rep.backend("std::thread (flume::unbounded)");
+        run_backlog(&mut rep, backlog::new_backlog_flume, consumer);
+
+        rep.backend("std::thread (crossbeam::channel::unbounded)");
+        run_backlog(&mut rep, backlog::new_backlog_crossbeam, consumer);
+
+        rep.backend("std::thread (kanal::unbounded)");
+        run_backlog(&mut rep, backlog::new_backlog_kanal, consumer);
+
+        rep.backend("std::thread (async_std::channel::unbounded)");
+        run_backlog(&mut rep, backlog::new_backlog_async_std, consumer);
+
+        rep.backend("std::thread (tokio::sync::mpsc::unbounded_channel)");
+        run_backlog(&mut rep, backlog::new_backlog_tokio, consumer);
+
+        rep.backend("std::thread (async_channel::unbounded)");
+        run_backlog(&mut rep, backlog::new_backlog_smol, consumer);
+
+        rep.backend("std::thread (futures::channel::mpsc::unbounded)");
+        run_backlog(&mut rep, backlog::new_backlog_futures, consumer);
+    }
+
+    rep.scenario("shutdown (disconnect)", Axis::Single);
+    for (name, backend) in shutdown::BACKENDS {
+        rep.backend(name);
+        run_shutdown(&mut rep, |repeat| backend.disconnect(repeat));
+    }
+
+    rep.scenario("shutdown (drop full)", Axis::Single);
+    for (name, backend) in shutdown::BACKENDS {
+        rep.backend(name);
+        run_shutdown(&mut rep, |repeat| backend.drop_full(repeat));
     }
 
     println!();
-    println!("mutex");
-    println!("std::sync::Mutex");
-    for i in [4, 8, 12, 16, 20, 24] {
-        let mut hdl = std_thread::MutexBench::new(i);
-        let start = SystemTime::now();
-        hdl.start();
-        let dur = start.elapsed().unwrap();
-        let sec = dur.as_secs_f64();
+    println!("results are saved to {output}");
+
+    if let Some(name) = save_baseline {
+        let path = baseline::save(&rep.results, &name).unwrap();
+        println!("baseline {name} is saved to {}", path.display());
+    }
+
+    if let Some(name) = baseline {
+        if compare_to_baseline(&rep.results, &name, threshold) {
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Load a result file given on the command line, or exit with 2 as for a missing baseline.
+fn load(input: &str) -> ResultSet {
+    ResultSet::load(input).unwrap_or_else(|e| {
+        eprintln!("cannot read {input}: {e}");
+        std::process::exit(2);
+    })
+}
+
+fn cannot_write(path: &str, e: std::io::Error) -> ! {
+    eprintln!("cannot write {path}: {e}");
+    std::process::exit(2);
+}
+
+/// Print changes of `current` from the named baseline,
+/// and return whether any point regressed or is missing from `current`.
+fn compare_to_baseline(current: &ResultSet, name: &str, threshold: f64) -> bool {
+    let baseline = match baseline::load(name) {
+        Ok(baseline) => baseline,
+        Err(e) => {
+            eprintln!("failed to load baseline {name}: {e}");
+            std::process::exit(2);
+        }
+    };
+    if baseline.count != current.count {
         println!(
-            "n = {:>2}: {:>10} [ops/s]",
-            i,
-            ((MAX_COUNT * i) as f64 / sec) as usize
+            "warning: baseline {name} was run with count = {}, but this run with {}",
+            baseline.count, current.count
         );
     }
 
-    println!("parking_lot::Mutex");
-    for i in [4, 8, 12, 16, 20, 24] {
-        let mut hdl = std_thread::MutexBenchPackingLot::new(i);
-        let start = SystemTime::now();
-        hdl.start();
-        let dur = start.elapsed().unwrap();
-        let sec = dur.as_secs_f64();
+    let comparisons = baseline::compare(&baseline, current, threshold);
+    let with = |verdict: baseline::Verdict| -> Vec<&baseline::Comparison> {
+        comparisons
+            .iter()
+            .filter(|c| c.verdict == verdict)
+            .collect()
+    };
+    let regressed = with(baseline::Verdict::Regressed);
+    let missing = with(baseline::Verdict::Missing);
+    let improved = with(baseline::Verdict::Improved);
+
+    println!();
+    println!(
+        "compared {} points to baseline {name}: {} regressed, {} missing, {} improved",
+        comparisons.len(),
+        regressed.len(),
+        missing.len(),
+        improved.len()
+    );
+    for (title, v) in [
+        ("regressed", &regressed),
+        ("missing", &missing),
+        ("improved", &improved),
+    ] {
+        if !v.is_empty() {
+            println!("{title}");
+            for c in v.iter() {
+                println!("  {c}");
+            }
+        }
+    }
+
+    let once = comparisons
+        .iter()
+        .filter(|c| c.current.is_some() && !c.repeated)
+        .count();
+    if once > 0 {
+        println!();
         println!(
-            "n = {:>2}: {:>10} [ops/s]",
-            i,
-            ((MAX_COUNT * i) as f64 / sec) as usize
+            "WARNING: {once} points were measured once in baseline {name} or in this run, so their noise is unknown"
         );
+        println!(
+            "WARNING: and only the threshold of {:.1}% applies to them. Run both with --samples 3 or more.",
+            threshold * 100.0
+        );
+    }
+
+    comparisons.iter().any(|c| c.verdict.is_failure())
+}
+
+fn usage(msg: &str) -> ! {
+    eprintln!("{msg}");
+    eprintln!("usage: async_bench [--output <result.json>] [--count <messages>] [--samples <k>]");
+    eprintln!("                   [--burst <messages>] [--gap <ms>]");
+    eprintln!("                   [--save-baseline <name>] [--baseline <name>] [--threshold <%>]");
+    eprintln!("       async_bench compare <result.json> --baseline <name> [--threshold <%>]");
+    eprintln!("       async_bench plot <result.json> [--output <dir>]");
+    eprintln!("       async_bench report <result.json> [--output <report.md>]");
+    eprintln!("       async_bench dashboard <result.json>... [--output <dashboard.html>]");
+    std::process::exit(2);
+}
+
+fn arg_value(arg: &str, value: Option<String>) -> String {
+    value.unwrap_or_else(|| usage(&format!("{arg} takes a value")))
+}
+
+/// Run every backend of a registered scenario for every n, timing only the run.
+/// Each point is repeated `rep.samples` times, and the median repetition is recorded.
+/// Backends with a reference are compared to the fastest of its backends with the same n.
+fn run_registered(rep: &mut Reporter, runtimes: &Runtimes, scenario: &registry::Scenario) {
+    rep.scenario(&scenario.name, scenario.axis);
+    let count = (scenario.count)(rep.count());
+    if count != rep.count() {
+        println!("Count: {count} messages per sender");
+    }
+    for backend in &scenario.backends {
+        rep.backend(&backend.name);
+        for &i in scenario.range {
+            let mut runs = Vec::new();
+            for _ in 0..rep.samples {
+                let run = match (backend.setup)(runtimes, i, count) {
+                    Ok(run) => run,
+                    Err(e) => {
+                        println!("n = {i:>2}: skipped: {e}");
+                        break;
+                    }
+                };
+                let start = Stopwatch::start();
+
+                let latency = run();
+
+                let mut r = rep.new_record(i, (scenario.ops)(i, count), start.stop());
+                r.count = count;
+                r.metric = scenario.metric;
+                r.latency = latency;
+                runs.push(r);
+            }
+
+            if runs.is_empty() {
+                continue;
+            }
+            let mut r = median(runs);
+
+            if let Some(reference) = backend.reference {
+                let fastest = reference
+                    .backends
+                    .iter()
+                    .filter_map(|b| rep.rate(reference.scenario, b, i))
+                    .reduce(f64::max);
+                if let Some(fastest) = fastest {
+                    r.extra
+                        .insert(reference.label.to_string(), r.ops_per_sec / fastest);
+                }
+            }
+            rep.push(r);
+        }
     }
+}
 
-    println!("async_std::sync::Mutex");
-    for i in [4, 8, 12, 16, 20, 24] {
-        async_std::task::block_on(async {
-            let mut hdl = async_std_bench::MutexBench::new(i);
-            let start = SystemTime::now();
-            hdl.start().await;
-            let dur = start.elapsed().unwrap();
-            let sec = dur.as_secs_f64();
-            println!(
-                "n = {:>2}: {:>10} [ops/s]",
-                i,
-                ((MAX_COUNT * i) as f64 / sec) as usize
-            );
-        });
-    }
-
-    println!("tokio::sync::Mutex");
-    for i in [4, 8, 12, 16, 20, 24] {
-        runtime.block_on(async {
-            let mut hdl = tokio_bench::MutexBench::new(i);
-            let start = SystemTime::now();
-            hdl.start().await;
-            let dur = start.elapsed().unwrap();
-            let sec = dur.as_secs_f64();
-            println!(
-                "n = {:>2}: {:>10} [ops/s]",
-                i,
-                ((MAX_COUNT * i) as f64 / sec) as usize
-            );
-        });
+/// The median of repetitions of a point by `Record::value()`, with the values of all of them as samples.
+fn median(mut runs: Vec<Record>) -> Record {
+    let samples: Vec<f64> = runs.iter().filter_map(Record::value).collect();
+    runs.sort_by(|a, b| {
+        let (a, b) = (a.value().unwrap_or(0.0), b.value().unwrap_or(0.0));
+        a.total_cmp(&b)
+    });
+    let mut r = runs.swap_remove(runs.len() / 2);
+    if samples.len() > 1 {
+        r.samples = samples;
     }
+    r
 }
 
-fn run_one_to_one(f: fn(usize) -> std_thread::OneToOne) {
-    for i in EVAL_RANGE {
-        let mut h = f(*i);
-        let start = SystemTime::now();
+/// Measure the latency of a shutdown `SHUTDOWN_REPEAT` times, repeated `rep.samples` times.
+fn run_shutdown(rep: &mut Reporter, f: impl Fn(usize) -> Latency) {
+    let runs = (0..rep.samples)
+        .map(|_| rep.latency_record(1, SHUTDOWN_REPEAT, f(SHUTDOWN_REPEAT)))
+        .collect();
+    rep.push(median(runs));
+}
 
-        h.start();
+/// Sweep target rates of an open-loop scenario, doubling them until
+/// the achieved rate falls below 90% of the target, i.e., the channel is saturated.
+fn run_open_loop(rep: &mut Reporter, f: fn(usize, usize) -> open_loop::OpenLoop) {
+    let mut rate = 10_000;
+    while rate <= MAX_RATE {
+        let mut h = f(rate, rep.count());
+        let count = h.count();
+        let start = Stopwatch::start();
 
-        let dur = start.elapsed().unwrap();
-        let sec = dur.as_secs_f64();
-        println!(
-            "n = {:>2}: {:>10} [ops/s]",
-            i,
-            (MAX_COUNT as f64 / sec) as usize
-        );
+        let latency = h.start();
+
+        let mut r = rep.new_record(rate, count, start.stop());
+        r.count = count;
+        r.metric = Metric::Latency;
+        r.latency = Some(latency);
+        let saturated = r.ops_per_sec < rate as f64 * 0.9;
+        rep.push(r);
+
+        if saturated {
+            break;
+        }
+        rate *= 2;
     }
 }
 
-fn run_many_to_one(f: fn(usize) -> std_thread::ManyToOne) {
-    for i in EVAL_RANGE[1..].iter() {
-        let mut h = f(*i);
-        let start = SystemTime::now();
+/// Run a backlog scenario, which has a single pair.
+/// The peak heap is recorded as well with the `alloc-count` feature.
+fn run_backlog(
+    rep: &mut Reporter,
+    f: fn(usize, backlog::Consumer) -> backlog::Backlog,
+    consumer: backlog::Consumer,
+) {
+    let count = rep.count();
+    let mut h = f(count, consumer);
+    let start = Stopwatch::start();
 
-        h.start();
+    let report = h.start();
 
-        let dur = start.elapsed().unwrap();
-        let sec = dur.as_secs_f64();
-        println!(
-            "n = {:>2}: {:>10} [ops/s]",
-            i,
-            ((MAX_COUNT / 10 * *i) as f64 / sec) as usize
-        );
+    let mut r = rep.new_record(1, count, start.stop());
+    r.metric = Metric::Other;
+    r.extra
+        .insert("peak depth".to_string(), report.peak_depth as f64);
+    r.extra.insert(
+        "produce [ms]".to_string(),
+        report.produce.as_secs_f64() * 1000.0,
+    );
+    r.extra.insert(
+        "drain [ms]".to_string(),
+        report.drain.as_secs_f64() * 1000.0,
+    );
+    r.series = report
+        .depth
+        .iter()
+        .map(|(t, d)| (t.as_secs_f64(), *d as f64))
+        .collect();
+    rep.push(r);
+}
+
+/// Prints measurements as they are taken, and collects them into a result set.
+struct Reporter {
+    results: ResultSet,
+    /// Repetitions of each point of registered and shutdown scenarios.
+    samples: usize,
+    /// Where the result set is saved after every record,
+    /// so that a run which panics keeps what it has measured.
+    output: Option<String>,
+    scenario: String,
+    axis: Axis,
+    backend: String,
+}
+
+impl Reporter {
+    fn new(results: ResultSet) -> Self {
+        Reporter {
+            results,
+            samples: 1,
+            output: None,
+            scenario: String::new(),
+            axis: Axis::default(),
+            backend: String::new(),
+        }
+    }
+
+    /// Messages per sender, or locks per task, of a run.
+    fn count(&self) -> usize {
+        self.results.count
+    }
+
+    /// Start a scenario, whose n counts `axis`.
+    fn scenario(&mut self, name: &str, axis: Axis) {
+        println!();
+        println!("{name}");
+        self.scenario = name.to_string();
+        self.axis = axis;
+    }
+
+    fn backend(&mut self, name: &str) {
+        println!("{name}");
+        self.backend = name.to_string();
+    }
+
+    /// A record of the current scenario and backend,
+    /// in which `ops` operations took `elapsed`, of `count()` messages per sender.
+    fn new_record(&self, n: usize, ops: usize, elapsed: Elapsed) -> Record {
+        let mut r = Record::new(&self.scenario, &self.backend, n, ops, elapsed.wall);
+        r.axis = self.axis;
+        r.count = self.count();
+        r.rusage = Some(elapsed.usage);
+        r.alloc = elapsed.alloc;
+        r
+    }
+
+    /// A record of the current scenario and backend with latency alone, of `samples` measurements.
+    fn latency_record(&self, n: usize, samples: usize, latency: Latency) -> Record {
+        let mut r = Record::latency(&self.scenario, &self.backend, n, samples, latency);
+        r.axis = self.axis;
+        r
+    }
+
+    fn push(&mut self, r: Record) {
+        println!("{r}");
+        self.results.records.push(r);
+        if let Some(path) = &self.output {
+            self.results.save(path).unwrap();
+        }
+    }
+
+    /// ops/s of a recorded backend with n.
+    fn rate(&self, scenario: &str, backend: &str, n: usize) -> Option<f64> {
+        self.results
+            .get(scenario, backend, n)
+            .map(|r| r.ops_per_sec)
     }
 }
@@ -0,0 +1,172 @@
+//! One-to-one communications with an open-loop producer,
+//! which sends messages on a fixed schedule at a target rate
+//! instead of as fast as possible.
+//!
+//! Every message carries the time it was scheduled to be sent,
+//! and latency is measured from that time.
+//! So, if the producer falls behind the schedule because the channel is saturated,
+//! the delay is counted as latency rather than hidden by sending less often.
+
+use crate::{
+    latency::Latency,
+    std_thread::{Rx, Tx},
+};
+use std::{
+    cell::RefCell,
+    sync::{Arc, Barrier},
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+/// How long a run takes at the target rate, unless it needs more than the given maximum number of messages.
+const RUN_TIME: Duration = Duration::from_millis(500);
+
+/// The timer sleeps until this much before a deadline, and spins after that,
+/// because sleeping is not precise enough for high rates.
+const SPIN_THRESHOLD: Duration = Duration::from_micros(200);
+
+pub struct OpenLoop {
+    handler: Vec<JoinHandle<Vec<Duration>>>,
+    barrier: Arc<Barrier>,
+    count: usize,
+}
+
+impl OpenLoop {
+    /// A pair sending `rate` messages per second, up to `max_count` messages.
+    pub fn new(rate: usize, max_count: usize, mkch: fn() -> (Tx, Rx)) -> Self {
+        let count = ((rate as f64 * RUN_TIME.as_secs_f64()) as usize).clamp(1, max_count);
+        let interval = Duration::from_secs_f64(1.0 / rate as f64);
+
+        let (tx, rx) = mkch();
+        let barrier = Arc::new(Barrier::new(3));
+
+        // Timestamps are sent as nanoseconds since `epoch`.
+        let epoch = Instant::now();
+
+        // Create a sender.
+        let bar = barrier.clone();
+        let sender = std::thread::spawn(move || {
+            bar.wait();
+            let start = Instant::now();
+            for i in 0..count {
+                let scheduled = start + interval.mul_f64(i as f64);
+                wait_until(scheduled);
+                tx((scheduled - epoch).as_nanos() as usize);
+            }
+            Vec::new()
+        });
+
+        // Create a receiver.
+        let bar = barrier.clone();
+        let receiver = std::thread::spawn(move || {
+            bar.wait();
+            let mut v = Vec::with_capacity(count);
+            for _ in 0..count {
+                let scheduled = epoch + Duration::from_nanos(rx() as u64);
+                v.push(scheduled.elapsed());
+            }
+            v
+        });
+
+        Self {
+            handler: vec![sender, receiver],
+            barrier,
+            count,
+        }
+    }
+
+    /// Number of messages sent in a run.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Run the pair and return the latency of all the messages.
+    pub fn start(&mut self) -> Latency {
+        self.barrier.wait();
+
+        let mut v = Vec::new();
+        for th in std::mem::take(&mut self.handler) {
+            v.extend(th.join().unwrap());
+        }
+
+        Latency::from_samples(v)
+    }
+}
+
+fn wait_until(deadline: Instant) {
+    loop {
+        let now = Instant::now();
+        if now >= deadline {
+            return;
+        }
+
+        let rest = deadline - now;
+        if rest > SPIN_THRESHOLD {
+            std::thread::sleep(rest - SPIN_THRESHOLD);
+        } else {
+            std::hint::spin_loop();
+        }
+    }
+}
+
+pub fn new_open_loop_sync_channel(rate: usize, max_count: usize) -> OpenLoop {
+    fn mkch() -> (Tx, Rx) {
+        let (tx, rx) = std::sync::mpsc::sync_channel(1024);
+        (
+            Box::new(move |x| tx.send(x).unwrap()),
+            Box::new(move || rx.recv().unwrap()),
+        )
+    }
+
+    OpenLoop::new(rate, max_count, mkch)
+}
+
+pub fn new_open_loop_flume(rate: usize, max_count: usize) -> OpenLoop {
+    fn mkch() -> (Tx, Rx) {
+        let (tx, rx) = flume::bounded(1024);
+        (
+            Box::new(move |x| tx.send(x).unwrap()),
+            Box::new(move || rx.recv().unwrap()),
+        )
+    }
+
+    OpenLoop::new(rate, max_count, mkch)
+}
+
+pub fn new_open_loop_crossbeam(rate: usize, max_count: usize) -> OpenLoop {
+    fn mkch() -> (Tx, Rx) {
+        let (tx, rx) = crossbeam::channel::bounded(1024);
+        (
+            Box::new(move |x| tx.send(x).unwrap()),
+            Box::new(move || rx.recv().unwrap()),
+        )
+    }
+
+    OpenLoop::new(rate, max_count, mkch)
+}
+
+pub fn new_open_loop_kanal(rate: usize, max_count: usize) -> OpenLoop {
+    fn mkch() -> (Tx, Rx) {
+        let (tx, rx) = kanal::bounded(1024);
+        (
+            Box::new(move |x| tx.send(x).unwrap()),
+            Box::new(move || rx.recv().unwrap()),
+        )
+    }
+
+    OpenLoop::new(rate, max_count, mkch)
+}
+
+/// `tokio::sync::mpsc::channel` between threads, with `blocking_send` and `blocking_recv`.
+pub fn new_open_loop_tokio(rate: usize, max_count: usize) -> OpenLoop {
+    fn mkch() -> (Tx, Rx) {
+        let (tx, rx) = tokio::sync::mpsc::channel(1024);
+        let rx = RefCell::new(rx);
+        (
+            Box::new(move |x| tx.blocking_send(x).unwrap()),
+            Box::new(move || rx.borrow_mut().blocking_recv().unwrap()),
+        )
+    }
+
+    OpenLoop::new(rate, max_count, mkch)
+}
@@ -0,0 +1,365 @@
+//! Line charts of throughput versus n, rendered to SVG from a result set,
+//! and of latency versus achieved throughput of the open-loop sweeps.
+//!
+//! Charts of related scenarios are placed side by side, as the figures in `figs/`,
+//! so those figures can be regenerated from a run.
+
+use crate::results::{Axis, Metric, ResultSet};
+use std::{fmt::Write, fs, io, path::Path};
+
+/// Figures of the README, as (file stem, scenarios side by side).
+pub const FIGURES: &[(&str, &[&str])] = &[
+    ("1to1", &["one-to-one (unbounded)", "one-to-one (bounded)"]),
+    ("Nto1", &["many-to-one"]),
+    ("mutex", &["mutex"]),
+];
+
+/// Colours of series, in order.
+pub const PALETTE: &[&str] = &[
+    "#3399ff", "#f0b020", "#55d040", "#ff2a1a", "#8c8c8c", "#9b59b6", "#1abc9c", "#e67e22",
+    "#34495e", "#e84393", "#7f8c2d", "#00a8c6", "#a0522d", "#2c3e90", "#c0392b",
+];
+
+const WIDTH: f64 = 800.0;
+const PLOT_HEIGHT: f64 = 320.0;
+const MARGIN_LEFT: f64 = 100.0;
+const MARGIN_RIGHT: f64 = 30.0;
+const LEGEND_ROW: f64 = 18.0;
+/// Rough width of a character of the legend, to decide how many columns fit.
+const LEGEND_CHAR: f64 = 6.5;
+
+pub struct Series {
+    pub name: String,
+    /// (x, y) sorted by n, e.g., (n, ops/s).
+    pub points: Vec<(f64, f64)>,
+}
+
+pub struct Chart {
+    pub title: String,
+    pub x_label: String,
+    pub y_label: String,
+    pub series: Vec<Series>,
+    /// Label the x axis at even steps from 0 rather than at every x of the points,
+    /// which differ between series if x is measured.
+    pub even_x: bool,
+}
+
+impl Chart {
+    /// Throughput of every backend of a scenario, in the order they were run.
+    pub fn throughput(results: &ResultSet, scenario: &str) -> Self {
+        let mut series: Vec<Series> = Vec::new();
+        for r in results.records.iter().filter(|r| r.scenario == scenario) {
+            let point = (r.n as f64, r.ops_per_sec);
+            match series.iter_mut().find(|s| s.name == r.backend) {
+                Some(s) => s.points.push(point),
+                None => series.push(Series {
+                    name: r.backend.clone(),
+                    points: vec![point],
+                }),
+            }
+        }
+        for s in series.iter_mut() {
+            s.points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        }
+
+        Chart {
+            title: scenario.to_string(),
+            x_label: results.axis(scenario).label().to_string(),
+            y_label: "[ops/s]".to_string(),
+            series,
+            even_x: false,
+        }
+    }
+
+    /// Latency, p50 or p99, of every backend of an open-loop sweep against its achieved rate,
+    /// i.e., a latency-vs-throughput curve, which bends back once the backend saturates.
+    pub fn latency_vs_rate(results: &ResultSet, scenario: &str, p99: bool) -> Self {
+        let mut records: Vec<_> = results
+            .records
+            .iter()
+            .filter(|r| r.scenario == scenario)
+            .collect();
+        records.sort_by_key(|r| r.n);
+
+        let mut series: Vec<Series> = Vec::new();
+        for r in records {
+            let Some(latency) = r.latency else {
+                continue;
+            };
+            let latency = if p99 { latency.p99 } else { latency.p50 };
+            let point = (r.ops_per_sec, latency.as_secs_f64() * 1e6);
+            match series.iter_mut().find(|s| s.name == r.backend) {
+                Some(s) => s.points.push(point),
+                None => series.push(Series {
+                    name: r.backend.clone(),
+                    points: vec![point],
+                }),
+            }
+        }
+
+        Chart {
+            title: format!("{scenario}: {}", if p99 { "p99" } else { "p50" }),
+            x_label: "achieved rate [msgs/s]".to_string(),
+            y_label: "latency [us]".to_string(),
+            series,
+            even_x: true,
+        }
+    }
+
+    /// Two columns of legend, unless a backend name is too long for them.
+    fn legend_columns(&self) -> usize {
+        let longest = self.series.iter().map(|s| s.name.len()).max().unwrap_or(0);
+        if longest as f64 * LEGEND_CHAR + 30.0 < (WIDTH - 40.0) / 2.0 {
+            2
+        } else {
+            1
+        }
+    }
+
+    fn legend_height(&self) -> f64 {
+        self.series.len().div_ceil(self.legend_columns()) as f64 * LEGEND_ROW + 10.0
+    }
+
+    /// Height without the legend.
+    fn body_height() -> f64 {
+        PLOT_HEIGHT + 80.0
+    }
+
+    fn render(&self, out: &mut String, x0: f64, legend_height: f64) {
+        // Legend.
+        let columns = self.legend_columns();
+        let column = (WIDTH - 40.0) / columns as f64;
+        for (i, s) in self.series.iter().enumerate() {
+            let x = x0 + 20.0 + (i % columns) as f64 * column;
+            let y = 16.0 + (i / columns) as f64 * LEGEND_ROW;
+            let color = PALETTE[i % PALETTE.len()];
+            let _ = writeln!(
+                out,
+                r#"<line x1="{x:.1}" y1="{y:.1}" x2="{:.1}" y2="{y:.1}" stroke="{color}" stroke-width="4"/>"#,
+                x + 16.0
+            );
+            let _ = writeln!(
+                out,
+                r#"<text x="{:.1}" y="{:.1}" font-size="11">{}</text>"#,
+                x + 22.0,
+                y + 4.0,
+                escape(&s.name)
+            );
+        }
+
+        let top = legend_height + 10.0;
+        let left = x0 + MARGIN_LEFT;
+        let right = x0 + WIDTH - MARGIN_RIGHT;
+        let bottom = top + PLOT_HEIGHT;
+
+        let xs: Vec<f64> = {
+            let mut v: Vec<f64> = self
+                .series
+                .iter()
+                .flat_map(|s| s.points.iter().map(|p| p.0))
+                .collect();
+            v.sort_by(f64::total_cmp);
+            v.dedup();
+            v
+        };
+        let (x_min, x_max) = match (xs.first(), xs.last()) {
+            _ if self.even_x => (0.0, y_ticks(xs.last().copied().unwrap_or(0.0)).1),
+            (Some(a), Some(b)) if a < b => (*a, *b),
+            (Some(a), _) => (*a - 1.0, *a + 1.0),
+            _ => (0.0, 1.0),
+        };
+        let y_max = self
+            .series
+            .iter()
+            .flat_map(|s| s.points.iter().map(|p| p.1))
+            .fold(0.0, f64::max);
+        let (step, y_top) = y_ticks(y_max);
+
+        let sx = |x: f64| left + (x - x_min) / (x_max - x_min) * (right - left);
+        let sy = |y: f64| bottom - y / y_top * PLOT_HEIGHT;
+
+        // Grid and y-axis labels.
+        let mut y = 0.0;
+        while y <= y_top + step / 2.0 {
+            let py = sy(y);
+            let _ = writeln!(
+                out,
+                r##"<line x1="{left:.1}" y1="{py:.1}" x2="{right:.1}" y2="{py:.1}" stroke="#d0d0d0"/>"##
+            );
+            let _ = writeln!(
+                out,
+                r#"<text x="{:.1}" y="{:.1}" font-size="12" text-anchor="end">{}</text>"#,
+                left - 8.0,
+                py + 4.0,
+                y as u64
+            );
+            y += step;
+        }
+        let _ = writeln!(
+            out,
+            r#"<text x="{:.1}" y="{:.1}" font-size="12" text-anchor="middle" transform="rotate(-90 {:.1} {:.1})">{}</text>"#,
+            x0 + 20.0,
+            top + PLOT_HEIGHT / 2.0,
+            x0 + 20.0,
+            top + PLOT_HEIGHT / 2.0,
+            escape(&self.y_label)
+        );
+
+        // x-axis labels at every n, or at even steps.
+        let x_labels = if self.even_x {
+            let (step, x_top) = y_ticks(x_max);
+            (0..=(x_top / step).round() as usize)
+                .map(|i| i as f64 * step)
+                .collect()
+        } else {
+            xs.clone()
+        };
+        for x in x_labels.iter() {
+            let _ = writeln!(
+                out,
+                r#"<text x="{:.1}" y="{:.1}" font-size="12" text-anchor="middle">{}</text>"#,
+                sx(*x),
+                bottom + 18.0,
+                x
+            );
+        }
+        let _ = writeln!(
+            out,
+            r#"<text x="{:.1}" y="{:.1}" font-size="12" text-anchor="middle">{}</text>"#,
+            (left + right) / 2.0,
+            bottom + 36.0,
+            escape(&self.x_label)
+        );
+        let _ = writeln!(
+            out,
+            r#"<text x="{:.1}" y="{:.1}" font-size="14" text-anchor="middle">{}</text>"#,
+            (left + right) / 2.0,
+            bottom + 66.0,
+            escape(&self.title)
+        );
+
+        // Lines.
+        for (i, s) in self.series.iter().enumerate() {
+            let color = PALETTE[i % PALETTE.len()];
+            let points: Vec<String> = s
+                .points
+                .iter()
+                .map(|(x, y)| format!("{:.1},{:.1}", sx(*x), sy(*y)))
+                .collect();
+            let _ = writeln!(
+                out,
+                r#"<polyline points="{}" fill="none" stroke="{color}" stroke-width="3" stroke-linejoin="round"><title>{}</title></polyline>"#,
+                points.join(" "),
+                escape(&s.name)
+            );
+        }
+    }
+}
+
+/// Render charts side by side into an SVG document.
+pub fn render(charts: &[Chart]) -> String {
+    let legend_height = charts.iter().map(Chart::legend_height).fold(0.0, f64::max);
+    let width = WIDTH * charts.len() as f64;
+    let height = legend_height + Chart::body_height();
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="sans-serif">"#
+    );
+    let _ = writeln!(
+        out,
+        r#"<rect width="{width}" height="{height}" fill="white"/>"#
+    );
+    for (i, chart) in charts.iter().enumerate() {
+        chart.render(&mut out, WIDTH * i as f64, legend_height);
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+/// Write the figures of the README, a chart of every other scenario with several values of n,
+/// and p50 and p99 latency against the achieved rate of every open-loop sweep, side by side,
+/// into `dir`. Returns the written files.
+pub fn write_all<P: AsRef<Path>>(results: &ResultSet, dir: P) -> io::Result<Vec<String>> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+
+    let scenarios = results.scenarios();
+
+    let mut written = Vec::new();
+    let mut write = |stem: &str, charts: Vec<Chart>| -> io::Result<()> {
+        let path = dir.join(format!("{stem}.svg"));
+        fs::write(&path, render(&charts))?;
+        written.push(path.display().to_string());
+        Ok(())
+    };
+
+    for (stem, figure) in FIGURES {
+        let charts: Vec<Chart> = figure
+            .iter()
+            .filter(|s| scenarios.iter().any(|(name, _)| name == *s))
+            .map(|s| Chart::throughput(results, s))
+            .collect();
+        if !charts.is_empty() {
+            write(stem, charts)?;
+        }
+    }
+
+    for (scenario, ns) in scenarios.iter() {
+        if results.axis(scenario) == Axis::Rate && results.metric(scenario) == Metric::Latency {
+            let charts = vec![
+                Chart::latency_vs_rate(results, scenario, false),
+                Chart::latency_vs_rate(results, scenario, true),
+            ];
+            write(&format!("{}_latency", slug(scenario)), charts)?;
+            continue;
+        }
+        let in_figure = FIGURES.iter().any(|(_, s)| s.contains(scenario));
+        // Scenarios whose n is not a number of threads, e.g., a target rate,
+        // or which do not measure throughput are not charted.
+        let charted = results.axis(scenario).is_concurrency()
+            && results.metric(scenario) == Metric::Throughput;
+        if in_figure || ns.len() < 2 || !charted {
+            continue;
+        }
+        write(&slug(scenario), vec![Chart::throughput(results, scenario)])?;
+    }
+
+    Ok(written)
+}
+
+/// A step of 1, 2 or 5 times a power of 10 giving about 4 intervals, and the top of the axis.
+fn y_ticks(max: f64) -> (f64, f64) {
+    if max <= 0.0 {
+        return (1.0, 1.0);
+    }
+    let raw = max / 4.0;
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|m| m * magnitude)
+        .find(|s| *s >= raw)
+        .unwrap_or(10.0 * magnitude);
+    (step, (max / step).ceil() * step)
+}
+
+/// A file name of a scenario, e.g., "one-to-one (batch)" -> "one-to-one_batch".
+fn slug(s: &str) -> String {
+    let mut out = String::new();
+    for c in s.chars() {
+        if c.is_ascii_alphanumeric() || c == '-' {
+            out.push(c);
+        } else if !out.ends_with('_') {
+            out.push('_');
+        }
+    }
+    out.trim_matches('_').to_string()
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
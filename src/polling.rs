@@ -0,0 +1,208 @@
+//! One-to-one communications polling channels with `try_send` and `try_recv`
+//! instead of blocking on them.
+//!
+//! When a channel is full or empty, senders and receivers back off
+//! as `Backoff` specifies and then try again.
+
+use std::{
+    cell::RefCell,
+    sync::{Arc, Barrier},
+    thread::JoinHandle,
+    time::Duration,
+};
+
+/// Give the message back if the channel is full.
+/// A disconnected channel panics instead, so that a run fails rather than retrying forever
+/// when the other side has panicked.
+pub type TryTx = Box<dyn Fn(usize) -> Result<(), usize> + Send>;
+/// `None` if the channel is empty, and a panic if it is disconnected.
+pub type TryRx = Box<dyn Fn() -> Option<usize> + Send>;
+
+const DISCONNECTED: &str = "the other side of the channel has been dropped";
+
+/// How long a thread parks at most in `Backoff::SpinPark`.
+/// Nobody unparks a polling thread, so it wakes up by this timeout.
+const PARK_TIMEOUT: Duration = Duration::from_micros(50);
+
+#[derive(Debug, Clone, Copy)]
+pub enum Backoff {
+    /// Keep spinning.
+    Spin,
+    /// Spin for a while, then yield the CPU on every retry.
+    SpinYield,
+    /// Spin for a while, yield for a while, then park with a timeout.
+    SpinPark,
+}
+
+impl Backoff {
+    pub const ALL: [Backoff; 3] = [Backoff::Spin, Backoff::SpinYield, Backoff::SpinPark];
+
+    fn wait(&self, b: &crossbeam::utils::Backoff) {
+        match self {
+            Backoff::Spin => b.spin(),
+            Backoff::SpinYield => b.snooze(),
+            Backoff::SpinPark => {
+                if b.is_completed() {
+                    std::thread::park_timeout(PARK_TIMEOUT);
+                } else {
+                    b.snooze();
+                }
+            }
+        }
+    }
+}
+
+pub struct OneToOnePolling {
+    handler: Vec<JoinHandle<usize>>,
+    barrier: Arc<Barrier>,
+}
+
+impl OneToOnePolling {
+    pub fn new(n: usize, count: usize, backoff: Backoff, mkch: fn() -> (TryTx, TryRx)) -> Self {
+        let mut v = Vec::new();
+
+        let barrier = Arc::new(Barrier::new(n * 2 + 1));
+
+        for _ in 0..n {
+            let (tx, rx) = mkch();
+
+            // Create a sender.
+            let bar = barrier.clone();
+            let th = std::thread::spawn(move || {
+                bar.wait();
+                for _ in 0..count {
+                    let b = crossbeam::utils::Backoff::new();
+                    let mut msg = 1;
+                    while let Err(m) = tx(msg) {
+                        msg = m;
+                        backoff.wait(&b);
+                    }
+                }
+                0
+            });
+            v.push(th);
+
+            // Create a receiver.
+            let bar = barrier.clone();
+            let th = std::thread::spawn(move || {
+                bar.wait();
+                let mut cnt = 0;
+                for _ in 0..count {
+                    let b = crossbeam::utils::Backoff::new();
+                    let n = loop {
+                        match rx() {
+                            Some(n) => break n,
+                            None => backoff.wait(&b),
+                        }
+                    };
+                    cnt += n;
+                }
+                cnt
+            });
+            v.push(th);
+        }
+
+        Self {
+            handler: v,
+            barrier,
+        }
+    }
+
+    pub fn start(&mut self) {
+        self.barrier.wait();
+        let v = std::mem::take(&mut self.handler);
+        for th in v {
+            th.join().unwrap();
+        }
+    }
+}
+
+pub fn new_one_to_one_sync_channel(n: usize, count: usize, backoff: Backoff) -> OneToOnePolling {
+    fn mkch() -> (TryTx, TryRx) {
+        use std::sync::mpsc::{TryRecvError, TrySendError};
+
+        let (tx, rx) = std::sync::mpsc::sync_channel(1024);
+        (
+            Box::new(move |x| match tx.try_send(x) {
+                Ok(()) => Ok(()),
+                Err(TrySendError::Full(x)) => Err(x),
+                Err(TrySendError::Disconnected(_)) => panic!("{DISCONNECTED}"),
+            }),
+            Box::new(move || match rx.try_recv() {
+                Ok(x) => Some(x),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => panic!("{DISCONNECTED}"),
+            }),
+        )
+    }
+
+    OneToOnePolling::new(n, count, backoff, mkch)
+}
+
+pub fn new_one_to_one_flume(n: usize, count: usize, backoff: Backoff) -> OneToOnePolling {
+    fn mkch() -> (TryTx, TryRx) {
+        use flume::{TryRecvError, TrySendError};
+
+        let (tx, rx) = flume::bounded(1024);
+        (
+            Box::new(move |x| match tx.try_send(x) {
+                Ok(()) => Ok(()),
+                Err(TrySendError::Full(x)) => Err(x),
+                Err(TrySendError::Disconnected(_)) => panic!("{DISCONNECTED}"),
+            }),
+            Box::new(move || match rx.try_recv() {
+                Ok(x) => Some(x),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => panic!("{DISCONNECTED}"),
+            }),
+        )
+    }
+
+    OneToOnePolling::new(n, count, backoff, mkch)
+}
+
+pub fn new_one_to_one_crossbeam(n: usize, count: usize, backoff: Backoff) -> OneToOnePolling {
+    fn mkch() -> (TryTx, TryRx) {
+        use crossbeam::channel::{TryRecvError, TrySendError};
+
+        let (tx, rx) = crossbeam::channel::bounded(1024);
+        (
+            Box::new(move |x| match tx.try_send(x) {
+                Ok(()) => Ok(()),
+                Err(TrySendError::Full(x)) => Err(x),
+                Err(TrySendError::Disconnected(_)) => panic!("{DISCONNECTED}"),
+            }),
+            Box::new(move || match rx.try_recv() {
+                Ok(x) => Some(x),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => panic!("{DISCONNECTED}"),
+            }),
+        )
+    }
+
+    OneToOnePolling::new(n, count, backoff, mkch)
+}
+
+/// `tokio::sync::mpsc::channel` polled from threads.
+pub fn new_one_to_one_tokio(n: usize, count: usize, backoff: Backoff) -> OneToOnePolling {
+    fn mkch() -> (TryTx, TryRx) {
+        use tokio::sync::mpsc::error::{TryRecvError, TrySendError};
+
+        let (tx, rx) = tokio::sync::mpsc::channel(1024);
+        let rx = RefCell::new(rx);
+        (
+            Box::new(move |x| match tx.try_send(x) {
+                Ok(()) => Ok(()),
+                Err(TrySendError::Full(x)) => Err(x),
+                Err(TrySendError::Closed(_)) => panic!("{DISCONNECTED}"),
+            }),
+            Box::new(move || match rx.borrow_mut().try_recv() {
+                Ok(x) => Some(x),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => panic!("{DISCONNECTED}"),
+            }),
+        )
+    }
+
+    OneToOnePolling::new(n, count, backoff, mkch)
+}
@@ -0,0 +1,1064 @@
+//! Scenarios, their backends and values of n, shared by the binary and the criterion benches.
+//!
+//! Scenarios with their own parameters or metrics, e.g., open-loop or backlog,
+//! are run by the binary directly.
+
+#[cfg(target_os = "linux")]
+use crate::{affinity, glommio_bench};
+use crate::{
+    async_std_bench, batch, bridge, futures_bench,
+    latency::Latency,
+    polling,
+    results::{Axis, Metric},
+    smol_bench, std_thread, thread_crossbeam, thread_flume, thread_kanal, thread_ringbuf,
+    thread_spsc, thread_thingbuf, tokio_bench,
+    workload::{self, Workload},
+};
+use futures::executor::ThreadPool;
+use std::io;
+#[cfg(target_os = "linux")]
+use std::rc::Rc;
+use tokio::runtime::Handle;
+
+pub const EVAL_RANGE: &[usize] = &[1, 4, 8, 12, 16, 20, 24];
+
+/// Values of n of scenarios with many senders.
+pub const MANY_RANGE: &[usize] = &[4, 8, 12, 16, 20, 24];
+
+/// Runtimes shared by async backends.
+pub struct Runtimes {
+    pub tokio: tokio::runtime::Runtime,
+    pub pool: ThreadPool,
+}
+
+impl Runtimes {
+    pub fn new() -> Self {
+        Runtimes {
+            tokio: tokio::runtime::Runtime::new().unwrap(),
+            pool: ThreadPool::new().unwrap(),
+        }
+    }
+}
+
+impl Default for Runtimes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Release spawned threads or tasks and wait for them,
+/// returning the latency of messages if the scenario samples it.
+pub type Run = Box<dyn FnOnce() -> Option<Latency>>;
+
+/// Spawn threads or tasks for n, each of which sends or locks count times,
+/// and which wait until `Run` is called.
+/// Fails if the backend cannot run with n on this machine, e.g., for lack of cores.
+pub type Setup = Box<dyn Fn(&Runtimes, usize, usize) -> io::Result<Run>>;
+
+pub struct Backend {
+    pub name: String,
+    pub setup: Setup,
+    /// Backends this one is compared to, if any.
+    pub reference: Option<Reference>,
+    /// Benchmark ID in criterion, if not `name`.
+    #[allow(dead_code)] // read by the benches alone, not by the binary
+    pub criterion_name: Option<&'static str>,
+}
+
+/// Backends of a scenario to the fastest of which another backend is compared with the same n.
+/// The ratio of ops/s is recorded in `Record::extra` as `label`.
+#[derive(Debug, Clone, Copy)]
+pub struct Reference {
+    pub label: &'static str,
+    pub scenario: &'static str,
+    pub backends: &'static [&'static str],
+}
+
+pub struct Scenario {
+    pub name: String,
+    /// Group in criterion, if not `name`.
+    /// Groups published before the registry keep their names,
+    /// so that their figures are regenerated at the same paths.
+    #[allow(dead_code)] // read by the benches alone, not by the binary
+    pub criterion_name: Option<&'static str>,
+    /// What n counts.
+    pub axis: Axis,
+    /// What runs measure. Backends of latency scenarios return the latency from `Run`.
+    pub metric: Metric,
+    pub range: &'static [usize],
+    /// Messages or locks per sender, given the count of the run.
+    pub count: fn(usize) -> usize,
+    /// Number of operations of a run with n and the messages or locks per sender.
+    pub ops: fn(usize, usize) -> usize,
+    pub backends: Vec<Backend>,
+}
+
+const ONE_TO_ONE_BOUNDED: &str = "one-to-one (bounded)";
+
+// Backends of `ONE_TO_ONE_BOUNDED` which others are compared to.
+const SPSC_SPIN: &str = "std::thread (spsc::channel, spin)";
+const SPSC_PARK: &str = "std::thread (spsc::channel, park)";
+const SYNC_CHANNEL: &str = "std::thread (std::sync::mpsc::sync_channel)";
+const FLUME_BOUNDED: &str = "std::thread (flume::bounded)";
+const CROSSBEAM_BOUNDED: &str = "std::thread (crossbeam::channel::bounded)";
+const KANAL_BOUNDED: &str = "std::thread (kanal::bounded)";
+const TOKIO_CHANNEL: &str = "tokio (tokio::sync::mpsc::channel)";
+
+/// The reference ring buffers, the lower bound of one-to-one channels.
+const SPSC: Reference = Reference {
+    label: "ratio to spsc",
+    scenario: ONE_TO_ONE_BOUNDED,
+    backends: &[SPSC_SPIN, SPSC_PARK],
+};
+
+/// The per-message loop of the same channel as a batched backend.
+const fn per_message(backend: &'static &'static str) -> Reference {
+    Reference {
+        label: "ratio to per-message",
+        scenario: ONE_TO_ONE_BOUNDED,
+        backends: std::slice::from_ref(backend),
+    }
+}
+
+/// Bursts of 1024 messages separated by 1 ms, long enough for receivers to park,
+/// unless `--burst` or `--gap` is given.
+/// Gaps are the same for every backend as long as they are no shorter than `workload::MIN_GAP`.
+pub const BURSTY: Workload = Workload::Bursty {
+    burst: 1024,
+    gap: workload::MIN_GAP,
+};
+
+fn backend(
+    name: impl Into<String>,
+    setup: impl Fn(&Runtimes, usize, usize) -> Run + 'static,
+) -> Backend {
+    Backend {
+        name: name.into(),
+        setup: Box::new(move |r, n, count| Ok(setup(r, n, count))),
+        reference: None,
+        criterion_name: None,
+    }
+}
+
+/// A backend which may be unable to run on this machine.
+#[cfg(target_os = "linux")]
+fn fallible(
+    name: impl Into<String>,
+    setup: impl Fn(&Runtimes, usize, usize) -> io::Result<Run> + 'static,
+) -> Backend {
+    Backend {
+        name: name.into(),
+        setup: Box::new(setup),
+        reference: None,
+        criterion_name: None,
+    }
+}
+
+impl Backend {
+    fn relative_to(self, reference: Reference) -> Self {
+        Backend {
+            reference: Some(reference),
+            ..self
+        }
+    }
+
+    fn criterion_name(self, name: &'static str) -> Self {
+        Backend {
+            criterion_name: Some(name),
+            ..self
+        }
+    }
+}
+
+fn sync<H: 'static>(mut h: H, start: fn(&mut H)) -> Run {
+    Box::new(move || {
+        start(&mut h);
+        None
+    })
+}
+
+/// Scenarios whose bursty senders send as `bursty`, e.g., `BURSTY`.
+pub fn scenarios(bursty: Workload) -> Vec<Scenario> {
+    #[allow(unused_mut)]
+    let mut v = vec![
+        one_to_one_bounded(),
+        one_to_one_unbounded(),
+        many_to_one(),
+        select(),
+        bridge(),
+        bridge_latency(),
+        mutex(),
+        one_to_one_batch(),
+    ];
+
+    // Without sysfs, e.g., in some containers, CPUs cannot be told apart,
+    // so threads cannot be pinned as intended.
+    #[cfg(target_os = "linux")]
+    if let Ok(topology) = affinity::Topology::detect() {
+        v.push(one_to_one_pinned(topology));
+    }
+
+    v.extend([
+        one_to_one_polling(),
+        one_to_one_bursty(bursty),
+        many_to_one_bursty(bursty),
+    ]);
+    v
+}
+
+fn one_to_one_bounded() -> Scenario {
+    let mut backends = vec![
+        // The reference ring buffers come first, so that others can be compared to them.
+        backend(SPSC_SPIN, |_, n, count| {
+            sync(
+                thread_spsc::new_one_to_one_spin(n, count),
+                std_thread::OneToOne::start,
+            )
+        }),
+        backend(SPSC_PARK, |_, n, count| {
+            sync(
+                thread_spsc::new_one_to_one_park(n, count),
+                std_thread::OneToOne::start,
+            )
+        }),
+        backend(SYNC_CHANNEL, |_, n, count| {
+            sync(
+                std_thread::new_one_to_one_sync_channel(n, count),
+                std_thread::OneToOne::start,
+            )
+        })
+        .criterion_name("std"),
+        backend(FLUME_BOUNDED, |_, n, count| {
+            sync(
+                thread_flume::new_one_to_one_bounded(n, count),
+                std_thread::OneToOne::start,
+            )
+        })
+        .criterion_name("flume"),
+        backend(CROSSBEAM_BOUNDED, |_, n, count| {
+            sync(
+                thread_crossbeam::new_one_to_one_bounded(n, count),
+                std_thread::OneToOne::start,
+            )
+        })
+        .criterion_name("crossbeam"),
+        backend(KANAL_BOUNDED, |_, n, count| {
+            sync(
+                thread_kanal::new_one_to_one_bounded(n, count),
+                std_thread::OneToOne::start,
+            )
+        }),
+        backend("std::thread (ringbuf::HeapRb)", |_, n, count| {
+            sync(
+                thread_ringbuf::new_one_to_one_bounded(n, count),
+                std_thread::OneToOne::start,
+            )
+        }),
+        backend(
+            "std::thread (thingbuf::mpsc::blocking::channel)",
+            |_, n, count| {
+                sync(
+                    thread_thingbuf::new_one_to_one_bounded(n, count),
+                    std_thread::OneToOne::start,
+                )
+            },
+        ),
+        backend("async_std (async_std::channel::bounded)", |_, n, count| {
+            let mut h =
+                async_std::task::block_on(async_std_bench::new_one_to_one_bounded(n, count));
+            Box::new(move || {
+                async_std::task::block_on(h.start());
+                None
+            })
+        })
+        .criterion_name("async_std"),
+        backend(TOKIO_CHANNEL, |r, n, count| {
+            let rt = r.tokio.handle().clone();
+            let mut h = rt.block_on(tokio_bench::new_one_to_one_bounded(n, count));
+            Box::new(move || {
+                rt.block_on(h.start());
+                None
+            })
+        })
+        .criterion_name("tokio"),
+        backend("smol (async_channel::bounded)", |_, n, count| {
+            let mut h = smol::block_on(smol_bench::new_one_to_one_bounded(n, count));
+            Box::new(move || {
+                smol::block_on(h.start());
+                None
+            })
+        }),
+        backend(
+            "futures (futures::channel::mpsc::channel, ThreadPool)",
+            |r, n, count| {
+                let mut h = futures::executor::block_on(futures_bench::new_one_to_one_bounded(
+                    &r.pool, n, count,
+                ));
+                Box::new(move || {
+                    futures::executor::block_on(h.start());
+                    None
+                })
+            },
+        ),
+        backend(
+            "futures (futures::channel::mpsc::channel, tokio)",
+            |r, n, count| {
+                let rt = r.tokio.handle().clone();
+                let spawner = futures_bench::TokioSpawner(rt.clone());
+                let mut h = rt.block_on(futures_bench::new_one_to_one_bounded(&spawner, n, count));
+                Box::new(move || {
+                    rt.block_on(h.start());
+                    None
+                })
+            },
+        ),
+    ];
+
+    #[cfg(target_os = "linux")]
+    backends.extend([
+        fallible(
+            "glommio (glommio::channels::local_channel, same core)",
+            |_, n, count| {
+                Ok(sync(
+                    glommio_bench::new_one_to_one_same_core(n, count)?,
+                    glommio_bench::OneToOneGlommio::start,
+                ))
+            },
+        ),
+        fallible(
+            "glommio (glommio::channels::shared_channel, cross core)",
+            |_, n, count| {
+                Ok(sync(
+                    glommio_bench::new_one_to_one_cross_core(n, count)?,
+                    glommio_bench::OneToOneGlommio::start,
+                ))
+            },
+        ),
+    ]);
+
+    for b in backends.iter_mut().skip(2) {
+        b.reference = Some(SPSC);
+    }
+
+    Scenario {
+        name: ONE_TO_ONE_BOUNDED.to_string(),
+        criterion_name: Some("1 to 1 (bounded)"),
+        axis: Axis::Pairs,
+        metric: Metric::Throughput,
+        range: EVAL_RANGE,
+        count: |count| count,
+        ops: |_, count| count,
+        backends,
+    }
+}
+
+fn one_to_one_unbounded() -> Scenario {
+    let backends = vec![
+        backend("std::thread (std::sync::mpsc::channel)", |_, n, count| {
+            sync(
+                std_thread::new_one_to_one_channel(n, count),
+                std_thread::OneToOne::start,
+            )
+        })
+        .criterion_name("std"),
+        backend("std::thread (flume::unbounded)", |_, n, count| {
+            sync(
+                thread_flume::new_one_to_one_unbounded(n, count),
+                std_thread::OneToOne::start,
+            )
+        })
+        .criterion_name("flume"),
+        backend(
+            "std::thread (crossbeam::channel::unbounded)",
+            |_, n, count| {
+                sync(
+                    thread_crossbeam::new_one_to_one_unbounded(n, count),
+                    std_thread::OneToOne::start,
+                )
+            },
+        )
+        .criterion_name("crossbeam"),
+        backend("std::thread (kanal::unbounded)", |_, n, count| {
+            sync(
+                thread_kanal::new_one_to_one_unbounded(n, count),
+                std_thread::OneToOne::start,
+            )
+        }),
+        backend(
+            "async_std (async_std::channel::unbounded)",
+            |_, n, count| {
+                let mut h =
+                    async_std::task::block_on(async_std_bench::new_one_to_one_unbounded(n, count));
+                Box::new(move || {
+                    async_std::task::block_on(h.start());
+                    None
+                })
+            },
+        )
+        .criterion_name("async_std"),
+        backend(
+            "tokio (tokio::sync::mpsc::unbounded_channel)",
+            |r, n, count| {
+                let rt = r.tokio.handle().clone();
+                let mut h = rt.block_on(tokio_bench::new_one_to_one_unbounded(n, count));
+                Box::new(move || {
+                    rt.block_on(h.start());
+                    None
+                })
+            },
+        )
+        .criterion_name("tokio"),
+        backend("smol (async_channel::unbounded)", |_, n, count| {
+            let mut h = smol::block_on(smol_bench::new_one_to_one_unbounded(n, count));
+            Box::new(move || {
+                smol::block_on(h.start());
+                None
+            })
+        }),
+        backend(
+            "futures (futures::channel::mpsc::unbounded, ThreadPool)",
+            |r, n, count| {
+                let mut h = futures::executor::block_on(futures_bench::new_one_to_one_unbounded(
+                    &r.pool, n, count,
+                ));
+                Box::new(move || {
+                    futures::executor::block_on(h.start());
+                    None
+                })
+            },
+        ),
+        backend(
+            "futures (futures::channel::mpsc::unbounded, tokio)",
+            |r, n, count| {
+                let rt = r.tokio.handle().clone();
+                let spawner = futures_bench::TokioSpawner(rt.clone());
+                let mut h =
+                    rt.block_on(futures_bench::new_one_to_one_unbounded(&spawner, n, count));
+                Box::new(move || {
+                    rt.block_on(h.start());
+                    None
+                })
+            },
+        ),
+    ];
+
+    let backends = backends.into_iter().map(|b| b.relative_to(SPSC)).collect();
+
+    Scenario {
+        name: "one-to-one (unbounded)".to_string(),
+        criterion_name: Some("1 to 1 (unbounded)"),
+        axis: Axis::Pairs,
+        metric: Metric::Throughput,
+        range: EVAL_RANGE,
+        count: |count| count,
+        ops: |_, count| count,
+        backends,
+    }
+}
+
+fn many_to_one() -> Scenario {
+    let backends = vec![
+        backend(
+            "std::thread (std::sync::mpsc::sync_channel)",
+            |_, n, count| {
+                sync(
+                    std_thread::new_many_to_one_sync_channel(n, count),
+                    std_thread::ManyToOne::start,
+                )
+            },
+        )
+        .criterion_name("std"),
+        backend("std::thread (flume::bounded)", |_, n, count| {
+            sync(
+                thread_flume::new_many_to_one_bounded(n, count),
+                std_thread::ManyToOne::start,
+            )
+        })
+        .criterion_name("flume"),
+        backend(
+            "std::thread (crossbeam::channel::bounded)",
+            |_, n, count| {
+                sync(
+                    thread_crossbeam::new_many_to_one_bounded(n, count),
+                    std_thread::ManyToOne::start,
+                )
+            },
+        )
+        .criterion_name("crossbeam"),
+        backend("std::thread (kanal::bounded)", |_, n, count| {
+            sync(
+                thread_kanal::new_many_to_one_bounded(n, count),
+                std_thread::ManyToOne::start,
+            )
+        }),
+        backend(
+            "std::thread (thingbuf::mpsc::blocking::channel)",
+            |_, n, count| {
+                sync(
+                    thread_thingbuf::new_many_to_one_bounded(n, count),
+                    std_thread::ManyToOne::start,
+                )
+            },
+        ),
+        backend("async_std (async_std::channel::bounded)", |_, n, count| {
+            let mut h =
+                async_std::task::block_on(async_std_bench::new_many_to_one_bounded(n, count));
+            Box::new(move || {
+                async_std::task::block_on(h.start());
+                None
+            })
+        })
+        .criterion_name("async_std"),
+        backend("tokio (tokio::sync::mpsc::channel)", |r, n, count| {
+            let rt = r.tokio.handle().clone();
+            let mut h = rt.block_on(tokio_bench::new_many_to_one_bounded(n, count));
+            Box::new(move || {
+                rt.block_on(h.start());
+                None
+            })
+        })
+        .criterion_name("tokio"),
+        backend("smol (async_channel::bounded)", |_, n, count| {
+            let mut h = smol::block_on(smol_bench::new_many_to_one_bounded(n, count));
+            Box::new(move || {
+                smol::block_on(h.start());
+                None
+            })
+        }),
+        backend(
+            "futures (futures::channel::mpsc::channel, ThreadPool)",
+            |r, n, count| {
+                let mut h = futures::executor::block_on(futures_bench::new_many_to_one_bounded(
+                    &r.pool, n, count,
+                ));
+                Box::new(move || {
+                    futures::executor::block_on(h.start());
+                    None
+                })
+            },
+        ),
+        backend(
+            "futures (futures::channel::mpsc::channel, tokio)",
+            |r, n, count| {
+                let rt = r.tokio.handle().clone();
+                let spawner = futures_bench::TokioSpawner(rt.clone());
+                let mut h = rt.block_on(futures_bench::new_many_to_one_bounded(&spawner, n, count));
+                Box::new(move || {
+                    rt.block_on(h.start());
+                    None
+                })
+            },
+        ),
+    ];
+
+    Scenario {
+        name: "many-to-one".to_string(),
+        criterion_name: Some("many to 1 (bounded)"),
+        axis: Axis::Senders,
+        metric: Metric::Throughput,
+        range: MANY_RANGE,
+        count: |count| count / 10,
+        ops: |n, count| count * n,
+        backends,
+    }
+}
+
+fn select() -> Scenario {
+    let backends = vec![
+        backend("std::thread (crossbeam::channel::Select)", |_, n, count| {
+            sync(
+                thread_crossbeam::new_select_bounded(n, count),
+                std_thread::ManyToOne::start,
+            )
+        }),
+        backend("std::thread (flume::Selector)", |_, n, count| {
+            sync(
+                thread_flume::new_select_bounded(n, count),
+                std_thread::ManyToOne::start,
+            )
+        }),
+        backend("async_std (futures::stream::select_all)", |_, n, count| {
+            let mut h = async_std::task::block_on(async_std_bench::new_select_bounded(n, count));
+            Box::new(move || {
+                async_std::task::block_on(h.start());
+                None
+            })
+        }),
+        backend(
+            "tokio (tokio::sync::mpsc::Receiver::poll_recv)",
+            |r, n, count| {
+                let rt = r.tokio.handle().clone();
+                let mut h = rt.block_on(tokio_bench::new_select_bounded(n, count));
+                Box::new(move || {
+                    rt.block_on(h.start());
+                    None
+                })
+            },
+        ),
+        // The macros take a fixed number of branches, which is 4 here.
+        fallible("async_std (futures::select!)", |_, n, count| {
+            if n != 4 {
+                return Err(select4_only());
+            }
+            let mut h = async_std::task::block_on(async_std_bench::new_select4_bounded(count));
+            Ok(Box::new(move || {
+                async_std::task::block_on(h.start());
+                None
+            }))
+        }),
+        fallible("tokio (tokio::select!)", |r, n, count| {
+            if n != 4 {
+                return Err(select4_only());
+            }
+            let rt = r.tokio.handle().clone();
+            let mut h = rt.block_on(tokio_bench::new_select4_bounded(count));
+            Ok(Box::new(move || {
+                rt.block_on(h.start());
+                None
+            }))
+        }),
+    ];
+
+    Scenario {
+        name: "select".to_string(),
+        criterion_name: None,
+        axis: Axis::Senders,
+        metric: Metric::Throughput,
+        range: MANY_RANGE,
+        count: |count| count / 10,
+        ops: |n, count| count * n,
+        backends,
+    }
+}
+
+fn select4_only() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "the macro selects over 4 channels",
+    )
+}
+
+/// Routes of the bridge scenarios with senders paced as `pace`.
+fn bridge_backends(pace: bridge::Pace) -> Vec<Backend> {
+    type New = fn(&Handle, usize, usize, bridge::Pace) -> bridge::Bridge;
+    let routes: [(&'static str, New); 5] = [
+        (
+            "tokio -> std::thread (flume::bounded)",
+            bridge::new_tokio_to_thread_flume,
+        ),
+        (
+            "tokio -> std::thread (crossbeam::channel::unbounded)",
+            bridge::new_tokio_to_thread_crossbeam,
+        ),
+        (
+            "tokio -> std::thread (tokio::sync::mpsc::channel)",
+            bridge::new_tokio_to_thread_tokio,
+        ),
+        (
+            "std::thread -> tokio (tokio::sync::mpsc::channel)",
+            bridge::new_thread_to_tokio_tokio,
+        ),
+        (
+            "std::thread -> tokio (flume::bounded)",
+            bridge::new_thread_to_tokio_flume,
+        ),
+    ];
+
+    routes
+        .into_iter()
+        .map(|(name, new)| {
+            backend(name, move |r, n, count| {
+                let mut h = new(r.tokio.handle(), n, count, pace);
+                Box::new(move || h.start())
+            })
+        })
+        .collect()
+}
+
+fn bridge() -> Scenario {
+    Scenario {
+        name: "async/sync bridge".to_string(),
+        criterion_name: None,
+        axis: Axis::Pairs,
+        metric: Metric::Throughput,
+        range: EVAL_RANGE,
+        count: |count| count,
+        ops: |_, count| count,
+        backends: bridge_backends(bridge::Pace::Saturate),
+    }
+}
+
+/// Latency of the bridge with senders paced at `bridge::PACED_RATE`,
+/// each sending for about half a second with the default count.
+fn bridge_latency() -> Scenario {
+    Scenario {
+        name: "async/sync bridge (latency)".to_string(),
+        criterion_name: None,
+        axis: Axis::Pairs,
+        metric: Metric::Latency,
+        range: EVAL_RANGE,
+        count: |count| (count / 200).max(1),
+        ops: |_, count| count,
+        backends: bridge_backends(bridge::Pace::Rate(bridge::PACED_RATE)),
+    }
+}
+
+fn mutex() -> Scenario {
+    let backends = vec![
+        backend("std::sync::Mutex", |_, n, count| {
+            sync(
+                std_thread::MutexBench::new(n, count),
+                std_thread::MutexBench::start,
+            )
+        })
+        .criterion_name("std"),
+        backend("parking_lot::Mutex", |_, n, count| {
+            sync(
+                std_thread::MutexBenchPackingLot::new(n, count),
+                std_thread::MutexBenchPackingLot::start,
+            )
+        })
+        .criterion_name("parking_lot"),
+        backend("async_std::sync::Mutex", |_, n, count| {
+            let mut h = async_std_bench::MutexBench::new(n, count);
+            Box::new(move || {
+                async_std::task::block_on(h.start());
+                None
+            })
+        })
+        .criterion_name("async_std"),
+        backend("tokio::sync::Mutex", |r, n, count| {
+            let rt = r.tokio.handle().clone();
+            let mut h = rt.block_on(async { tokio_bench::MutexBench::new(n, count) });
+            Box::new(move || {
+                rt.block_on(h.start());
+                None
+            })
+        })
+        .criterion_name("tokio"),
+        backend("async_lock::Mutex", |_, n, count| {
+            let mut h = smol_bench::MutexBench::new(n, count);
+            Box::new(move || {
+                smol::block_on(h.start());
+                None
+            })
+        }),
+        backend("futures::lock::Mutex (ThreadPool)", |r, n, count| {
+            let mut h = futures_bench::MutexBench::new(&r.pool, n, count);
+            Box::new(move || {
+                futures::executor::block_on(h.start());
+                None
+            })
+        }),
+        backend("futures::lock::Mutex (tokio)", |r, n, count| {
+            let rt = r.tokio.handle().clone();
+            let spawner = futures_bench::TokioSpawner(rt.clone());
+            let mut h = futures_bench::MutexBench::new(&spawner, n, count);
+            Box::new(move || {
+                rt.block_on(h.start());
+                None
+            })
+        }),
+    ];
+
+    Scenario {
+        name: "mutex".to_string(),
+        criterion_name: None,
+        axis: Axis::Threads,
+        metric: Metric::Throughput,
+        range: MANY_RANGE,
+        count: |count| count,
+        ops: |n, count| count * n,
+        backends,
+    }
+}
+
+fn one_to_one_batch() -> Scenario {
+    type New = fn(usize, usize) -> batch::OneToOneBatch;
+    let drain: [(&str, New, &'static &'static str); 4] = [
+        (
+            "std::thread (std::sync::mpsc::sync_channel, try_iter)",
+            batch::new_one_to_one_sync_channel,
+            &SYNC_CHANNEL,
+        ),
+        (
+            "std::thread (flume::bounded, drain)",
+            batch::new_one_to_one_flume,
+            &FLUME_BOUNDED,
+        ),
+        (
+            "std::thread (crossbeam::channel::bounded, try_iter)",
+            batch::new_one_to_one_crossbeam,
+            &CROSSBEAM_BOUNDED,
+        ),
+        (
+            "std::thread (kanal::bounded, try_recv)",
+            batch::new_one_to_one_kanal,
+            &KANAL_BOUNDED,
+        ),
+    ];
+    let send: [(&str, New, &'static &'static str); 4] = [
+        (
+            "std::thread (std::sync::mpsc::sync_channel, Vec<usize>)",
+            batch::new_batch_send_sync_channel,
+            &SYNC_CHANNEL,
+        ),
+        (
+            "std::thread (flume::bounded, Vec<usize>)",
+            batch::new_batch_send_flume,
+            &FLUME_BOUNDED,
+        ),
+        (
+            "std::thread (crossbeam::channel::bounded, Vec<usize>)",
+            batch::new_batch_send_crossbeam,
+            &CROSSBEAM_BOUNDED,
+        ),
+        (
+            "std::thread (kanal::bounded, Vec<usize>)",
+            batch::new_batch_send_kanal,
+            &KANAL_BOUNDED,
+        ),
+    ];
+    let threads = |v: [(&'static str, New, &'static &'static str); 4]| {
+        v.into_iter().map(|(name, new, per_message_backend)| {
+            backend(name, move |_, n, count| {
+                sync(new(n, count), batch::OneToOneBatch::start)
+            })
+            .relative_to(per_message(per_message_backend))
+        })
+    };
+
+    let mut backends: Vec<Backend> = threads(drain).collect();
+    backends.push(
+        backend(
+            "tokio (tokio::sync::mpsc::channel, recv_many)",
+            |r, n, count| {
+                let rt = r.tokio.handle().clone();
+                let mut h = rt.block_on(tokio_bench::new_one_to_one_recv_many(n, count));
+                Box::new(move || {
+                    rt.block_on(h.start());
+                    None
+                })
+            },
+        )
+        .relative_to(per_message(&TOKIO_CHANNEL)),
+    );
+    backends.extend(threads(send));
+
+    Scenario {
+        name: "one-to-one (batch)".to_string(),
+        criterion_name: None,
+        axis: Axis::Pairs,
+        metric: Metric::Throughput,
+        range: EVAL_RANGE,
+        count: |count| count,
+        ops: |_, count| count,
+        backends,
+    }
+}
+
+/// Pairs of threads pinned to CPUs as each `affinity::Pinning` specifies.
+/// Values of n for which the machine has not enough such CPUs are skipped.
+#[cfg(target_os = "linux")]
+fn one_to_one_pinned(topology: affinity::Topology) -> Scenario {
+    type New = fn(usize, usize) -> std_thread::OneToOne;
+    let channels: [(&str, New); 4] = [
+        (
+            "std::sync::mpsc::sync_channel",
+            std_thread::new_one_to_one_sync_channel,
+        ),
+        ("flume::bounded", thread_flume::new_one_to_one_bounded),
+        (
+            "crossbeam::channel::bounded",
+            thread_crossbeam::new_one_to_one_bounded,
+        ),
+        ("spsc::channel, park", thread_spsc::new_one_to_one_park),
+    ];
+
+    let topology = Rc::new(topology);
+    let mut backends = Vec::new();
+    for pinning in affinity::Pinning::ALL {
+        for (channel, new) in channels {
+            let topology = topology.clone();
+            backends.push(fallible(
+                format!("std::thread ({channel}, {pinning:?})"),
+                move |_, n, count| {
+                    let Some(cpus) = topology.pairs(pinning, n) else {
+                        return Err(io::Error::new(
+                            io::ErrorKind::Unsupported,
+                            "unavailable on this machine",
+                        ));
+                    };
+                    let mut h = new(n, count);
+                    h.pin(&cpus)?;
+                    Ok(sync(h, std_thread::OneToOne::start))
+                },
+            ));
+        }
+    }
+
+    Scenario {
+        name: "one-to-one (pinned)".to_string(),
+        criterion_name: None,
+        axis: Axis::Pairs,
+        metric: Metric::Throughput,
+        range: EVAL_RANGE,
+        count: |count| count,
+        ops: |_, count| count,
+        backends,
+    }
+}
+
+fn one_to_one_polling() -> Scenario {
+    type New = fn(usize, usize, polling::Backoff) -> polling::OneToOnePolling;
+    let channels: [(&str, New); 4] = [
+        (
+            "std::sync::mpsc::sync_channel",
+            polling::new_one_to_one_sync_channel,
+        ),
+        ("flume::bounded", polling::new_one_to_one_flume),
+        (
+            "crossbeam::channel::bounded",
+            polling::new_one_to_one_crossbeam,
+        ),
+        ("tokio::sync::mpsc::channel", polling::new_one_to_one_tokio),
+    ];
+
+    let mut backends = Vec::new();
+    for backoff in polling::Backoff::ALL {
+        for (channel, new) in channels {
+            backends.push(backend(
+                format!("std::thread ({channel}, try_send/try_recv, {backoff:?})"),
+                move |_, n, count| sync(new(n, count, backoff), polling::OneToOnePolling::start),
+            ));
+        }
+    }
+
+    Scenario {
+        name: "one-to-one (polling)".to_string(),
+        criterion_name: None,
+        axis: Axis::Pairs,
+        metric: Metric::Throughput,
+        range: EVAL_RANGE,
+        count: |count| count,
+        ops: |_, count| count,
+        backends,
+    }
+}
+
+/// Latency of draining each burst, from sending its first message until receiving its last,
+/// which includes waking up the parked receiver.
+fn one_to_one_bursty(bursty: Workload) -> Scenario {
+    let backends = vec![
+        backend(SYNC_CHANNEL, move |_, n, count| {
+            let mut h = std_thread::new_one_to_one_sync_channel_with(n, count, bursty);
+            Box::new(move || {
+                h.start();
+                h.drain_latency()
+            })
+        }),
+        backend(FLUME_BOUNDED, move |_, n, count| {
+            let mut h = thread_flume::new_one_to_one_bounded_with(n, count, bursty);
+            Box::new(move || {
+                h.start();
+                h.drain_latency()
+            })
+        }),
+        backend(CROSSBEAM_BOUNDED, move |_, n, count| {
+            let mut h = thread_crossbeam::new_one_to_one_bounded_with(n, count, bursty);
+            Box::new(move || {
+                h.start();
+                h.drain_latency()
+            })
+        }),
+        backend(
+            "async_std (async_std::channel::bounded)",
+            move |_, n, count| {
+                let mut h = async_std::task::block_on(
+                    async_std_bench::new_one_to_one_bounded_with(n, count, bursty),
+                );
+                Box::new(move || {
+                    async_std::task::block_on(h.start());
+                    h.drain_latency()
+                })
+            },
+        ),
+        backend(TOKIO_CHANNEL, move |r, n, count| {
+            let rt = r.tokio.handle().clone();
+            let mut h = rt.block_on(tokio_bench::new_one_to_one_bounded_with(n, count, bursty));
+            Box::new(move || {
+                rt.block_on(h.start());
+                h.drain_latency()
+            })
+        }),
+    ];
+
+    Scenario {
+        name: format!("one-to-one ({bursty})"),
+        criterion_name: None,
+        axis: Axis::Pairs,
+        metric: Metric::Latency,
+        range: EVAL_RANGE,
+        count: |count| count,
+        ops: |_, count| count,
+        backends,
+    }
+}
+
+/// Latency of draining each burst of every sender as `one_to_one_bursty`.
+fn many_to_one_bursty(bursty: Workload) -> Scenario {
+    let backends = vec![
+        backend(SYNC_CHANNEL, move |_, n, count| {
+            let mut h = std_thread::new_many_to_one_sync_channel_with(n, count, bursty);
+            Box::new(move || {
+                h.start();
+                h.drain_latency()
+            })
+        }),
+        backend(FLUME_BOUNDED, move |_, n, count| {
+            let mut h = thread_flume::new_many_to_one_bounded_with(n, count, bursty);
+            Box::new(move || {
+                h.start();
+                h.drain_latency()
+            })
+        }),
+        backend(CROSSBEAM_BOUNDED, move |_, n, count| {
+            let mut h = thread_crossbeam::new_many_to_one_bounded_with(n, count, bursty);
+            Box::new(move || {
+                h.start();
+                h.drain_latency()
+            })
+        }),
+        backend(
+            "async_std (async_std::channel::bounded)",
+            move |_, n, count| {
+                let mut h = async_std::task::block_on(
+                    async_std_bench::new_many_to_one_bounded_with(n, count, bursty),
+                );
+                Box::new(move || {
+                    async_std::task::block_on(h.start());
+                    h.drain_latency()
+                })
+            },
+        ),
+        backend(TOKIO_CHANNEL, move |r, n, count| {
+            let rt = r.tokio.handle().clone();
+            let mut h = rt.block_on(tokio_bench::new_many_to_one_bounded_with(n, count, bursty));
+            Box::new(move || {
+                rt.block_on(h.start());
+                h.drain_latency()
+            })
+        }),
+    ];
+
+    Scenario {
+        name: format!("many-to-one ({bursty})"),
+        criterion_name: None,
+        axis: Axis::Senders,
+        metric: Metric::Latency,
+        range: MANY_RANGE,
+        count: |count| count / 10,
+        ops: |n, count| count * n,
+        backends,
+    }
+}
@@ -0,0 +1,412 @@
+//! Markdown report of a result set: the environment, a table of throughput of every scenario,
+//! or of latency or of its own metrics if that is what the scenario measures,
+//! the best backend of each ranked scenario and where rankings change with n.
+//!
+//! A backend is taken to lead, or to overtake another, only if it is better beyond the noise,
+//! as `baseline` tells regressions from noise, so that a noisy point does not reorder backends.
+
+use crate::{
+    baseline,
+    results::{Metric, Record, ResultSet},
+};
+use std::fmt::Write;
+
+/// Crossovers are reported among this many of the best backends.
+const TOP: usize = 3;
+
+/// Differences below 5% are never taken as one backend being better than another.
+const MARGIN: f64 = baseline::DEFAULT_THRESHOLD;
+
+pub fn render(results: &ResultSet) -> String {
+    let mut out = String::new();
+    let scenarios = results.scenarios();
+
+    let _ = writeln!(out, "# Results of {}", date(results.timestamp));
+    let _ = writeln!(out);
+    let _ = writeln!(out, "## Environment");
+    let _ = writeln!(out);
+    let env = &results.environment;
+    let _ = writeln!(out, "- CPU: {}", env.cpu_model);
+    let _ = writeln!(out, "- Cores: {} cores, {} threads", env.cores, env.threads);
+    for c in env.caches.iter() {
+        let _ = writeln!(out, "- L{} cache ({}): {}", c.level, c.kind, c.size);
+    }
+    let _ = writeln!(out, "- Kernel: {}", env.kernel);
+    if let Some(governor) = &env.governor {
+        let _ = writeln!(out, "- Governor: {governor}");
+    }
+    let _ = writeln!(out, "- Rust: {} ({})", env.rustc, env.profile);
+    if !env.dependencies.is_empty() {
+        let deps: Vec<String> = env
+            .dependencies
+            .iter()
+            .map(|(name, version)| format!("{name} {version}"))
+            .collect();
+        let _ = writeln!(out, "- Dependencies: {}", deps.join(", "));
+    }
+    if results.count > 0 {
+        let _ = writeln!(
+            out,
+            "- Count: {} messages per sender, unless a scenario notes otherwise",
+            results.count
+        );
+    }
+    let _ = writeln!(out);
+
+    let _ = writeln!(out, "## Summary");
+    let _ = writeln!(out);
+    for (scenario, ns) in scenarios.iter() {
+        let leaders = leaders(results, scenario, ns);
+        if !leaders.is_empty() {
+            let _ = writeln!(
+                out,
+                "- {scenario}: {} {}",
+                best(results.metric(scenario)),
+                describe_leaders(&leaders, ns)
+            );
+        }
+    }
+    let _ = writeln!(out);
+
+    for (scenario, ns) in scenarios.iter() {
+        let metric = results.metric(scenario);
+        let _ = writeln!(out, "## {scenario}");
+        let _ = writeln!(out);
+        let caption = match metric {
+            Metric::Throughput => {
+                "Throughput [ops/s]; higher is better. The fastest of each n is in bold."
+            }
+            Metric::Latency => "p99 latency; lower is better. The lowest of each n is in bold.",
+            Metric::Other => "Metrics specific to the scenario, which are not ranked.",
+        };
+        let _ = writeln!(out, "{caption}");
+        match results.count_of(scenario) {
+            Some(count) if count != results.count => {
+                let _ = writeln!(out, "{count} messages per sender.");
+            }
+            _ => (),
+        }
+        let _ = writeln!(out);
+        if metric == Metric::Other {
+            write_extra(&mut out, results, scenario);
+            continue;
+        }
+
+        let header: Vec<String> = ns.iter().map(|n| format!("n = {n}")).collect();
+        let _ = writeln!(out, "| backend | {} |", header.join(" | "));
+        let _ = writeln!(out, "|---|{}", "---:|".repeat(ns.len()));
+
+        let best: Vec<Option<&str>> = ns.iter().map(|n| leader(results, scenario, *n)).collect();
+        for backend in results.backends(scenario) {
+            let cells: Vec<String> = ns
+                .iter()
+                .zip(best.iter())
+                .map(|(n, best)| match results.get(scenario, backend, *n) {
+                    Some(r) if *best == Some(backend) => format!("**{}**", cell(r)),
+                    Some(r) => cell(r),
+                    None => "-".to_string(),
+                })
+                .collect();
+            let _ = writeln!(out, "| `{backend}` | {} |", cells.join(" | "));
+        }
+        let _ = writeln!(out);
+
+        let leaders = leaders(results, scenario, ns);
+        if !leaders.is_empty() {
+            let best = match metric {
+                Metric::Throughput => "Fastest",
+                Metric::Latency => "Lowest p99 latency",
+                Metric::Other => unreachable!(),
+            };
+            let _ = writeln!(out, "- {best}: {}", describe_leaders(&leaders, ns));
+        }
+        for (a, b, overtaking, overtaken) in crossovers(results, scenario, ns) {
+            let _ = writeln!(
+                out,
+                "- From n = {a} to n = {b}, `{overtaking}` overtakes `{overtaken}`"
+            );
+        }
+        let _ = writeln!(out);
+    }
+
+    out
+}
+
+/// How the best backend of a scenario with a metric is called in the summary.
+fn best(metric: Metric) -> &'static str {
+    match metric {
+        Metric::Throughput => "fastest",
+        Metric::Latency => "lowest p99 latency",
+        Metric::Other => "",
+    }
+}
+
+/// The value a record is ranked by; higher is better.
+fn score(r: &Record) -> Option<f64> {
+    let value = r.value()?;
+    Some(match r.metric {
+        Metric::Latency => -value,
+        _ => value,
+    })
+}
+
+fn cell(r: &Record) -> String {
+    match (r.metric, &r.latency) {
+        (Metric::Throughput, _) => thousands(r.ops_per_sec),
+        (Metric::Latency, Some(l)) => format!("{:.3?}", l.p99),
+        (_, _) => "-".to_string(),
+    }
+}
+
+/// A table of the scenario specific metrics of every record of a scenario.
+fn write_extra(out: &mut String, results: &ResultSet, scenario: &str) {
+    let records: Vec<&Record> = results
+        .records
+        .iter()
+        .filter(|r| r.scenario == scenario)
+        .collect();
+    let mut keys: Vec<&str> = Vec::new();
+    for k in records.iter().flat_map(|r| r.extra.keys()) {
+        if !keys.contains(&k.as_str()) {
+            keys.push(k);
+        }
+    }
+
+    let _ = writeln!(out, "| backend | n | {} |", keys.join(" | "));
+    let _ = writeln!(out, "|---|---:|{}", "---:|".repeat(keys.len()));
+    for r in records {
+        let cells: Vec<String> = keys
+            .iter()
+            .map(|k| {
+                r.extra
+                    .get(*k)
+                    .map_or("-".to_string(), |v| format!("{v:.2}"))
+            })
+            .collect();
+        let _ = writeln!(out, "| `{}` | {} | {} |", r.backend, r.n, cells.join(" | "));
+    }
+    let _ = writeln!(out);
+}
+
+/// Backends of a scenario with n, from the best.
+fn ranking<'a>(results: &'a ResultSet, scenario: &str, n: usize) -> Vec<&'a str> {
+    let mut v: Vec<(&str, f64)> = results
+        .records
+        .iter()
+        .filter(|r| r.scenario == scenario && r.n == n)
+        .filter_map(|r| Some((r.backend.as_str(), score(r)?)))
+        .collect();
+    v.sort_by(|a, b| b.1.total_cmp(&a.1));
+    v.into_iter().map(|(backend, _)| backend).collect()
+}
+
+fn leader<'a>(results: &'a ResultSet, scenario: &str, n: usize) -> Option<&'a str> {
+    ranking(results, scenario, n).first().copied()
+}
+
+/// The best backend of each n, as runs of consecutive values of n: (backend, first, last).
+/// The leader of the previous n keeps leading unless the best of n is better beyond the noise,
+/// or the leader was not measured with n.
+fn leaders<'a>(
+    results: &'a ResultSet,
+    scenario: &str,
+    ns: &[usize],
+) -> Vec<(&'a str, usize, usize)> {
+    let mut v: Vec<(&str, usize, usize)> = Vec::new();
+    for n in ns {
+        let Some(best) = leader(results, scenario, *n) else {
+            continue;
+        };
+        match v.last_mut() {
+            Some((backend, _, last))
+                if *backend == best
+                    || results.get(scenario, backend, *n).is_some()
+                        && !is_better(results, scenario, *n, best, backend) =>
+            {
+                *last = *n
+            }
+            _ => v.push((best, *n, *n)),
+        }
+    }
+    v
+}
+
+/// Whether backend a is better than b with n beyond the noise of both:
+/// by more than `MARGIN`, and by more than `baseline::SIGMAS` standard deviations of their repetitions.
+fn is_better(results: &ResultSet, scenario: &str, n: usize, a: &str, b: &str) -> bool {
+    let (Some(a), Some(b)) = (results.get(scenario, a, n), results.get(scenario, b, n)) else {
+        return false;
+    };
+    let (Some(x), Some(y)) = (score(a), score(b)) else {
+        return false;
+    };
+    let noise = (a.stddev().unwrap_or(0.0).powi(2) + b.stddev().unwrap_or(0.0).powi(2)).sqrt();
+    x - y > (MARGIN * y.abs()).max(baseline::SIGMAS * noise)
+}
+
+fn describe_leaders(leaders: &[(&str, usize, usize)], ns: &[usize]) -> String {
+    if let [(backend, _, _)] = leaders {
+        return if ns.len() > 1 {
+            format!("`{backend}` at every n")
+        } else {
+            format!("`{backend}`")
+        };
+    }
+
+    let runs: Vec<String> = leaders
+        .iter()
+        .map(|(backend, first, last)| {
+            if first == last {
+                format!("`{backend}` at n = {first}")
+            } else {
+                format!("`{backend}` for n = {first} to {last}")
+            }
+        })
+        .collect();
+    runs.join(", ")
+}
+
+/// Pairs of backends whose order swaps as n grows,
+/// both among the `TOP` best of either n: (n, later n, overtaking, overtaken).
+///
+/// The order of two backends is taken only where one is better than the other beyond the noise,
+/// so a swap spans the values of n between which they are within the noise of each other.
+/// A swap which is undone at the next n, i.e., a single outlying point, is left out.
+fn crossovers<'a>(
+    results: &'a ResultSet,
+    scenario: &str,
+    ns: &[usize],
+) -> Vec<(usize, usize, &'a str, &'a str)> {
+    let backends = results.backends(scenario);
+    let mut v = Vec::new();
+    for (i, x) in backends.iter().enumerate() {
+        for y in backends.iter().skip(i + 1) {
+            // Values of n where the order is clear, and whether x is ahead there.
+            let clear: Vec<(usize, bool)> = ns
+                .iter()
+                .filter_map(|n| {
+                    if is_better(results, scenario, *n, x, y) {
+                        Some((*n, true))
+                    } else if is_better(results, scenario, *n, y, x) {
+                        Some((*n, false))
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            let swaps: Vec<(usize, usize, bool)> = clear
+                .windows(2)
+                .filter(|w| w[0].1 != w[1].1)
+                .map(|w| (w[0].0, w[1].0, w[1].1))
+                .collect();
+
+            let mut k = 0;
+            while k < swaps.len() {
+                let (a, b, x_ahead) = swaps[k];
+                if swaps.get(k + 1).is_some_and(|next| next.0 == b) {
+                    k += 2;
+                    continue;
+                }
+                k += 1;
+                let (overtaking, overtaken) = if x_ahead { (*x, *y) } else { (*y, *x) };
+                if among_top(results, scenario, a, overtaking, overtaken)
+                    || among_top(results, scenario, b, overtaking, overtaken)
+                {
+                    v.push((a, b, overtaking, overtaken));
+                }
+            }
+        }
+    }
+    v.sort_by_key(|(a, b, _, _)| (*a, *b));
+    v
+}
+
+/// Whether both backends are among the `TOP` best with n.
+fn among_top(results: &ResultSet, scenario: &str, n: usize, a: &str, b: &str) -> bool {
+    let top = ranking(results, scenario, n);
+    let top = &top[..top.len().min(TOP)];
+    top.contains(&a) && top.contains(&b)
+}
+
+/// Seconds since the UNIX epoch as a date and time in UTC, e.g., "2022-06-25 12:34:56 UTC".
+fn date(secs: u64) -> String {
+    let days = (secs / 86400) as i64;
+    let (h, m, s) = (secs % 86400 / 3600, secs % 3600 / 60, secs % 60);
+
+    // The civil calendar from days since 1970-01-01, after Howard Hinnant's `civil_from_days`.
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!("{year:04}-{month:02}-{day:02} {h:02}:{m:02}:{s:02} UTC")
+}
+
+/// e.g., 12345678.9 -> "12,345,679"
+fn thousands(x: f64) -> String {
+    let s = (x.round() as u64).to_string();
+    let mut out = String::new();
+    for (i, c) in s.chars().enumerate() {
+        if i > 0 && (s.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environment::Environment;
+    use std::time::Duration;
+
+    /// ops/s of backends with n = 1, 2, 3, ...
+    fn results(points: &[(&str, &[f64])]) -> ResultSet {
+        let mut results = ResultSet::new(Environment::default(), 1);
+        for (backend, rates) in points {
+            for (i, rate) in rates.iter().enumerate() {
+                let secs = Duration::from_secs_f64(1000.0 / rate);
+                results
+                    .records
+                    .push(Record::new("s", backend, i + 1, 1000, secs));
+            }
+        }
+        results
+    }
+
+    #[test]
+    fn one_noisy_point_does_not_reorder_backends() {
+        let results = results(&[
+            ("a", &[100.0, 100.0, 100.0, 100.0]),
+            ("b", &[90.0, 90.0, 40.0, 90.0]),
+            ("c", &[80.0, 82.0, 80.0, 82.0]),
+        ]);
+        let ns = [1, 2, 3, 4];
+        assert!(crossovers(&results, "s", &ns).is_empty());
+        assert_eq!(leaders(&results, "s", &ns), vec![("a", 1, 4)]);
+    }
+
+    #[test]
+    fn overtaking_through_a_tie_is_reported_once() {
+        let results = results(&[
+            ("a", &[100.0, 100.0, 100.0, 100.0]),
+            ("b", &[80.0, 102.0, 98.0, 130.0]),
+        ]);
+        let ns = [1, 2, 3, 4];
+        assert_eq!(crossovers(&results, "s", &ns), vec![(1, 4, "b", "a")]);
+        assert_eq!(leaders(&results, "s", &ns), vec![("a", 1, 3), ("b", 4, 4)]);
+    }
+
+    #[test]
+    fn dates_are_utc() {
+        assert_eq!(date(0), "1970-01-01 00:00:00 UTC");
+        assert_eq!(date(951782400), "2000-02-29 00:00:00 UTC");
+        assert_eq!(date(1656160496), "2022-06-25 12:34:56 UTC");
+    }
+}
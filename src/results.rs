@@ -0,0 +1,331 @@
+//! Result files: measurements of a run together with its environment.
+
+use crate::{
+    counting_alloc::AllocStats, environment::Environment, latency::Latency, rusage::Usage,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt, fs, io, path::Path, time::Duration};
+
+/// What a record measures, which decides how backends are ranked and compared.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    /// ops/s; higher is better.
+    #[default]
+    Throughput,
+    /// p99 of `latency`; lower is better.
+    /// ops/s, if any, is not ranked nor compared.
+    Latency,
+    /// Scenario specific metrics in `extra` and `series`, which are neither ranked nor compared.
+    Other,
+}
+
+/// What n of a record counts, i.e., the x-axis of a chart of its scenario.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Axis {
+    /// Pairs of a sender and a receiver.
+    #[default]
+    Pairs,
+    /// Senders to a single receiver.
+    Senders,
+    /// Threads or tasks contending for a lock.
+    Threads,
+    /// Target rate of messages per second.
+    Rate,
+    /// Nothing; a scenario with a single pair or measurement, whose n is always 1.
+    Single,
+}
+
+impl Axis {
+    pub fn label(self) -> &'static str {
+        match self {
+            Axis::Pairs => "#pairs",
+            Axis::Senders => "#senders",
+            Axis::Threads => "#threads",
+            Axis::Rate => "target rate [msgs/s]",
+            Axis::Single => "n",
+        }
+    }
+
+    /// Whether n counts threads or tasks, against which throughput is charted.
+    pub fn is_concurrency(self) -> bool {
+        matches!(self, Axis::Pairs | Axis::Senders | Axis::Threads)
+    }
+}
+
+/// A measurement of a backend in a scenario with n threads, tasks or pairs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Record {
+    pub scenario: String,
+    pub backend: String,
+    pub n: usize,
+    #[serde(default)]
+    pub axis: Axis,
+
+    #[serde(default)]
+    pub metric: Metric,
+
+    /// Number of operations, e.g., messages or lock acquisitions.
+    pub ops: usize,
+    /// Messages per sender, or locks per task, which scenarios with many senders divide;
+    /// 0 if not applicable or unknown, e.g., in result files of older versions.
+    #[serde(default)]
+    pub count: usize,
+    pub secs: f64,
+    pub ops_per_sec: f64,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rusage: Option<Usage>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency: Option<Latency>,
+
+    /// Only with the `alloc-count` feature.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alloc: Option<AllocStats>,
+
+    /// Scenario specific metrics.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, f64>,
+
+    /// A scenario specific metric over time, as (seconds since the start, value).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub series: Vec<(f64, f64)>,
+
+    /// `value()` of every repetition, if the measurement was repeated.
+    /// The other fields are of the median repetition.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub samples: Vec<f64>,
+}
+
+impl Record {
+    pub fn new(scenario: &str, backend: &str, n: usize, ops: usize, dur: Duration) -> Self {
+        let secs = dur.as_secs_f64();
+        Record {
+            scenario: scenario.to_string(),
+            backend: backend.to_string(),
+            n,
+            axis: Axis::Pairs,
+            metric: Metric::Throughput,
+            ops,
+            count: 0,
+            secs,
+            ops_per_sec: ops as f64 / secs,
+            rusage: None,
+            latency: None,
+            alloc: None,
+            extra: BTreeMap::new(),
+            series: Vec::new(),
+            samples: Vec::new(),
+        }
+    }
+
+    /// A record of latency alone, of `samples` measurements.
+    /// It has no throughput, because the elapsed time of such scenarios
+    /// includes waits and setup between measurements.
+    pub fn latency(
+        scenario: &str,
+        backend: &str,
+        n: usize,
+        samples: usize,
+        latency: Latency,
+    ) -> Self {
+        let mut r = Record::new(scenario, backend, n, samples, Duration::ZERO);
+        r.metric = Metric::Latency;
+        r.ops_per_sec = 0.0;
+        r.latency = Some(latency);
+        r
+    }
+
+    /// Whether ops/s was measured, i.e., this is not a record of latency alone.
+    pub fn has_throughput(&self) -> bool {
+        self.secs > 0.0
+    }
+
+    /// The value backends are ranked and compared by:
+    /// ops/s, or p99 latency in seconds if this records latency.
+    pub fn value(&self) -> Option<f64> {
+        match self.metric {
+            Metric::Throughput => Some(self.ops_per_sec),
+            Metric::Latency => self.latency.map(|l| l.p99.as_secs_f64()),
+            Metric::Other => None,
+        }
+    }
+
+    /// Sample standard deviation of `value()` over the repetitions.
+    pub fn stddev(&self) -> Option<f64> {
+        let k = self.samples.len();
+        if k < 2 {
+            return None;
+        }
+        let mean = self.samples.iter().sum::<f64>() / k as f64;
+        let var = self.samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (k - 1) as f64;
+        Some(var.sqrt())
+    }
+
+    /// Operations per second of CPU time spent by the whole process.
+    pub fn ops_per_cpu_sec(&self) -> Option<f64> {
+        let cpu = self.rusage?.cpu_secs();
+        (cpu > 0.0).then(|| self.ops as f64 / cpu)
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.has_throughput(), &self.latency) {
+            (false, Some(latency)) => write!(f, "n = {:>2}: {latency}", self.n)?,
+            _ => write!(
+                f,
+                "n = {:>2}: {:>10} [ops/s]",
+                self.n, self.ops_per_sec as usize
+            )?,
+        }
+        if let Some(usage) = &self.rusage {
+            if let Some(ops) = self.ops_per_cpu_sec() {
+                write!(f, ", {:>10} [ops/cpu-s]", ops as usize)?;
+            }
+            write!(
+                f,
+                ", cpu = {:.3}s (user {:.3}s, sys {:.3}s), csw = {}/{}",
+                usage.cpu_secs(),
+                usage.user_secs,
+                usage.system_secs,
+                usage.voluntary_switches,
+                usage.involuntary_switches
+            )?;
+        }
+        if let (true, Some(latency)) = (self.has_throughput(), &self.latency) {
+            write!(f, ", {latency}")?;
+        }
+        if let Some(alloc) = &self.alloc {
+            write!(
+                f,
+                ", allocs = {}, alloc bytes = {}, peak heap = {}",
+                alloc.allocations, alloc.bytes, alloc.peak_bytes
+            )?;
+        }
+        for (k, v) in self.extra.iter() {
+            write!(f, ", {k} = {v:.2}")?;
+        }
+        match (self.stddev(), self.metric) {
+            (Some(stddev), Metric::Latency) => write!(
+                f,
+                ", stddev = {:.3?} of p99 over {} runs",
+                Duration::from_secs_f64(stddev),
+                self.samples.len()
+            )?,
+            (Some(stddev), _) => write!(
+                f,
+                ", stddev = {} [ops/s] over {} runs",
+                stddev as usize,
+                self.samples.len()
+            )?,
+            (None, _) => (),
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultSet {
+    /// Seconds since the UNIX epoch when the run started.
+    pub timestamp: u64,
+    pub environment: Environment,
+    /// Messages per sender, or locks per task, of a run, i.e., `--count`.
+    /// Scenarios with many senders divide it, as recorded in `Record::count`.
+    #[serde(default)]
+    pub count: usize,
+    pub records: Vec<Record>,
+}
+
+impl ResultSet {
+    pub fn new(environment: Environment, count: usize) -> Self {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+
+        ResultSet {
+            timestamp,
+            environment,
+            count,
+            records: Vec::new(),
+        }
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        if let Some(dir) = path.as_ref().parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json)
+    }
+
+    /// Scenarios in the order they were run, with their values of n in ascending order.
+    pub fn scenarios(&self) -> Vec<(&str, Vec<usize>)> {
+        let mut v: Vec<(&str, Vec<usize>)> = Vec::new();
+        for r in self.records.iter() {
+            let i = match v.iter().position(|(s, _)| *s == r.scenario) {
+                Some(i) => i,
+                None => {
+                    v.push((&r.scenario, Vec::new()));
+                    v.len() - 1
+                }
+            };
+            if !v[i].1.contains(&r.n) {
+                v[i].1.push(r.n);
+            }
+        }
+        for (_, ns) in v.iter_mut() {
+            ns.sort_unstable();
+        }
+        v
+    }
+
+    /// Backends of a scenario in the order they were run.
+    pub fn backends(&self, scenario: &str) -> Vec<&str> {
+        let mut v: Vec<&str> = Vec::new();
+        for r in self.records.iter().filter(|r| r.scenario == scenario) {
+            if !v.contains(&r.backend.as_str()) {
+                v.push(&r.backend);
+            }
+        }
+        v
+    }
+
+    /// What n of a scenario counts.
+    pub fn axis(&self, scenario: &str) -> Axis {
+        self.records
+            .iter()
+            .find(|r| r.scenario == scenario)
+            .map_or(Axis::default(), |r| r.axis)
+    }
+
+    /// What the records of a scenario measure.
+    pub fn metric(&self, scenario: &str) -> Metric {
+        self.records
+            .iter()
+            .find(|r| r.scenario == scenario)
+            .map_or(Metric::default(), |r| r.metric)
+    }
+
+    /// Messages per sender, or locks per task, of a scenario, if recorded.
+    pub fn count_of(&self, scenario: &str) -> Option<usize> {
+        self.records
+            .iter()
+            .find(|r| r.scenario == scenario)
+            .map(|r| r.count)
+            .filter(|c| *c > 0)
+    }
+
+    pub fn get(&self, scenario: &str, backend: &str, n: usize) -> Option<&Record> {
+        self.records
+            .iter()
+            .find(|r| r.scenario == scenario && r.backend == backend && r.n == n)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let json = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&json)?)
+    }
+}
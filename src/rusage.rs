@@ -0,0 +1,83 @@
+//! CPU time and context switches of the process, from `getrusage`,
+//! and a stopwatch measuring them with wall-clock time and allocations.
+
+use crate::counting_alloc::{self, AllocStats};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Usage {
+    pub user_secs: f64,
+    pub system_secs: f64,
+    pub voluntary_switches: i64,
+    pub involuntary_switches: i64,
+}
+
+impl Usage {
+    #[cfg(unix)]
+    pub fn now() -> Self {
+        let mut ru: libc::rusage = unsafe { std::mem::zeroed() };
+        unsafe { libc::getrusage(libc::RUSAGE_SELF, &mut ru) };
+
+        let secs = |tv: libc::timeval| tv.tv_sec as f64 + tv.tv_usec as f64 / 1_000_000.0;
+        Usage {
+            user_secs: secs(ru.ru_utime),
+            system_secs: secs(ru.ru_stime),
+            voluntary_switches: ru.ru_nvcsw as i64,
+            involuntary_switches: ru.ru_nivcsw as i64,
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub fn now() -> Self {
+        Self::default()
+    }
+
+    /// Usage between `earlier` and `self`.
+    pub fn since(&self, earlier: &Usage) -> Usage {
+        Usage {
+            user_secs: self.user_secs - earlier.user_secs,
+            system_secs: self.system_secs - earlier.system_secs,
+            voluntary_switches: self.voluntary_switches - earlier.voluntary_switches,
+            involuntary_switches: self.involuntary_switches - earlier.involuntary_switches,
+        }
+    }
+
+    pub fn cpu_secs(&self) -> f64 {
+        self.user_secs + self.system_secs
+    }
+}
+
+/// Wall-clock time and resource usage of a measured region.
+pub struct Stopwatch {
+    wall: Instant,
+    usage: Usage,
+    alloc: Option<counting_alloc::Region>,
+}
+
+/// What a `Stopwatch` measured.
+pub struct Elapsed {
+    pub wall: Duration,
+    pub usage: Usage,
+    /// Only with the `alloc-count` feature.
+    pub alloc: Option<AllocStats>,
+}
+
+impl Stopwatch {
+    pub fn start() -> Self {
+        Stopwatch {
+            alloc: cfg!(feature = "alloc-count").then(counting_alloc::Region::start),
+            usage: Usage::now(),
+            wall: Instant::now(),
+        }
+    }
+
+    pub fn stop(&self) -> Elapsed {
+        let wall = self.wall.elapsed();
+        Elapsed {
+            wall,
+            usage: Usage::now().since(&self.usage),
+            alloc: self.alloc.as_ref().map(|r| r.stop()),
+        }
+    }
+}
@@ -0,0 +1,374 @@
+//! Cost of tearing channels down.
+//!
+//! - Disconnection: time from dropping the last sender until a receiver
+//!   blocked on the empty channel observes the disconnection.
+//! - Dropping a full channel: time to drop the last handle of a channel
+//!   holding `FULL` messages, which frees its buffer.
+//!
+//! Async receivers are waited for with `futures::executor::block_on` on a thread,
+//! so that every backend wakes a blocked thread.
+//! `ringbuf`, `spsc` and glommio's channels are not covered, because they
+//! have no notion of disconnection.
+
+use crate::latency::Latency;
+use futures::StreamExt;
+use std::{
+    sync::{Arc, Barrier},
+    time::{Duration, Instant},
+};
+
+/// Number of messages in a full channel, which is the capacity of bounded ones.
+pub const FULL: usize = 1024;
+
+/// Time for a receiver to block after it is ready, before the sender is dropped.
+const SETTLE: Duration = Duration::from_millis(1);
+
+/// A sender to drop, and a function receiving until the channel is disconnected.
+pub type Disconnect = (Box<dyn Send>, Box<dyn FnOnce() + Send>);
+
+/// A sender and a receiver of a channel holding `FULL` messages.
+pub type Full = (Box<dyn Send>, Box<dyn Send>);
+
+#[derive(Clone, Copy)]
+pub struct Shutdown {
+    disconnect: fn() -> Disconnect,
+    full: fn() -> Full,
+}
+
+impl Shutdown {
+    /// Latency from dropping the sender until the receiver returns, repeated `repeat` times.
+    pub fn disconnect(&self, repeat: usize) -> Latency {
+        let mut v = Vec::with_capacity(repeat);
+        for _ in 0..repeat {
+            let (tx, wait) = (self.disconnect)();
+            let barrier = Arc::new(Barrier::new(2));
+
+            let bar = barrier.clone();
+            let th = std::thread::spawn(move || {
+                bar.wait();
+                wait();
+                Instant::now()
+            });
+
+            barrier.wait();
+            std::thread::sleep(SETTLE);
+
+            let dropped = Instant::now();
+            drop(tx);
+            v.push(th.join().unwrap() - dropped);
+        }
+        Latency::from_samples(v)
+    }
+
+    /// Time to drop the receiver of a full channel after its sender, repeated `repeat` times.
+    pub fn drop_full(&self, repeat: usize) -> Latency {
+        let mut v = Vec::with_capacity(repeat);
+        for _ in 0..repeat {
+            let (tx, rx) = (self.full)();
+            drop(tx);
+
+            let start = Instant::now();
+            drop(rx);
+            v.push(start.elapsed());
+        }
+        Latency::from_samples(v)
+    }
+}
+
+pub const BACKENDS: &[(&str, Shutdown)] = &[
+    ("std::sync::mpsc::channel", STD_CHANNEL),
+    ("std::sync::mpsc::sync_channel", STD_SYNC_CHANNEL),
+    ("flume::unbounded", FLUME_UNBOUNDED),
+    ("flume::bounded", FLUME_BOUNDED),
+    ("crossbeam::channel::unbounded", CROSSBEAM_UNBOUNDED),
+    ("crossbeam::channel::bounded", CROSSBEAM_BOUNDED),
+    ("kanal::unbounded", KANAL_UNBOUNDED),
+    ("kanal::bounded", KANAL_BOUNDED),
+    ("thingbuf::mpsc::blocking::channel", THINGBUF),
+    ("async_std::channel::unbounded", ASYNC_STD_UNBOUNDED),
+    ("async_std::channel::bounded", ASYNC_STD_BOUNDED),
+    ("tokio::sync::mpsc::unbounded_channel", TOKIO_UNBOUNDED),
+    ("tokio::sync::mpsc::channel", TOKIO_BOUNDED),
+    ("async_channel::unbounded", ASYNC_CHANNEL_UNBOUNDED),
+    ("async_channel::bounded", ASYNC_CHANNEL_BOUNDED),
+    ("futures::channel::mpsc::unbounded", FUTURES_UNBOUNDED),
+    ("futures::channel::mpsc::channel", FUTURES_BOUNDED),
+];
+
+const STD_CHANNEL: Shutdown = Shutdown {
+    disconnect: || {
+        let (tx, rx) = std::sync::mpsc::channel::<usize>();
+        (Box::new(tx), Box::new(move || while rx.recv().is_ok() {}))
+    },
+    full: || {
+        let (tx, rx) = std::sync::mpsc::channel();
+        for i in 0..FULL {
+            tx.send(i).unwrap();
+        }
+        (Box::new(tx), Box::new(rx))
+    },
+};
+
+const STD_SYNC_CHANNEL: Shutdown = Shutdown {
+    disconnect: || {
+        let (tx, rx) = std::sync::mpsc::sync_channel::<usize>(FULL);
+        (Box::new(tx), Box::new(move || while rx.recv().is_ok() {}))
+    },
+    full: || {
+        let (tx, rx) = std::sync::mpsc::sync_channel(FULL);
+        for i in 0..FULL {
+            tx.send(i).unwrap();
+        }
+        (Box::new(tx), Box::new(rx))
+    },
+};
+
+const FLUME_UNBOUNDED: Shutdown = Shutdown {
+    disconnect: || {
+        let (tx, rx) = flume::unbounded::<usize>();
+        (Box::new(tx), Box::new(move || while rx.recv().is_ok() {}))
+    },
+    full: || {
+        let (tx, rx) = flume::unbounded();
+        for i in 0..FULL {
+            tx.send(i).unwrap();
+        }
+        (Box::new(tx), Box::new(rx))
+    },
+};
+
+const FLUME_BOUNDED: Shutdown = Shutdown {
+    disconnect: || {
+        let (tx, rx) = flume::bounded::<usize>(FULL);
+        (Box::new(tx), Box::new(move || while rx.recv().is_ok() {}))
+    },
+    full: || {
+        let (tx, rx) = flume::bounded(FULL);
+        for i in 0..FULL {
+            tx.send(i).unwrap();
+        }
+        (Box::new(tx), Box::new(rx))
+    },
+};
+
+const CROSSBEAM_UNBOUNDED: Shutdown = Shutdown {
+    disconnect: || {
+        let (tx, rx) = crossbeam::channel::unbounded::<usize>();
+        (Box::new(tx), Box::new(move || while rx.recv().is_ok() {}))
+    },
+    full: || {
+        let (tx, rx) = crossbeam::channel::unbounded();
+        for i in 0..FULL {
+            tx.send(i).unwrap();
+        }
+        (Box::new(tx), Box::new(rx))
+    },
+};
+
+const CROSSBEAM_BOUNDED: Shutdown = Shutdown {
+    disconnect: || {
+        let (tx, rx) = crossbeam::channel::bounded::<usize>(FULL);
+        (Box::new(tx), Box::new(move || while rx.recv().is_ok() {}))
+    },
+    full: || {
+        let (tx, rx) = crossbeam::channel::bounded(FULL);
+        for i in 0..FULL {
+            tx.send(i).unwrap();
+        }
+        (Box::new(tx), Box::new(rx))
+    },
+};
+
+const KANAL_UNBOUNDED: Shutdown = Shutdown {
+    disconnect: || {
+        let (tx, rx) = kanal::unbounded::<usize>();
+        (Box::new(tx), Box::new(move || while rx.recv().is_ok() {}))
+    },
+    full: || {
+        let (tx, rx) = kanal::unbounded();
+        for i in 0..FULL {
+            tx.send(i).unwrap();
+        }
+        (Box::new(tx), Box::new(rx))
+    },
+};
+
+const KANAL_BOUNDED: Shutdown = Shutdown {
+    disconnect: || {
+        let (tx, rx) = kanal::bounded::<usize>(FULL);
+        (Box::new(tx), Box::new(move || while rx.recv().is_ok() {}))
+    },
+    full: || {
+        let (tx, rx) = kanal::bounded(FULL);
+        for i in 0..FULL {
+            tx.send(i).unwrap();
+        }
+        (Box::new(tx), Box::new(rx))
+    },
+};
+
+const THINGBUF: Shutdown = Shutdown {
+    disconnect: || {
+        let (tx, rx) = thingbuf::mpsc::blocking::channel::<usize>(FULL);
+        (Box::new(tx), Box::new(move || while rx.recv().is_some() {}))
+    },
+    full: || {
+        let (tx, rx) = thingbuf::mpsc::blocking::channel(FULL);
+        for i in 0..FULL {
+            tx.send(i).unwrap();
+        }
+        (Box::new(tx), Box::new(rx))
+    },
+};
+
+const ASYNC_STD_UNBOUNDED: Shutdown = Shutdown {
+    disconnect: || {
+        let (tx, rx) = async_std::channel::unbounded::<usize>();
+        (
+            Box::new(tx),
+            Box::new(move || {
+                futures::executor::block_on(async { while rx.recv().await.is_ok() {} })
+            }),
+        )
+    },
+    full: || {
+        let (tx, rx) = async_std::channel::unbounded();
+        for i in 0..FULL {
+            tx.try_send(i).unwrap();
+        }
+        (Box::new(tx), Box::new(rx))
+    },
+};
+
+const ASYNC_STD_BOUNDED: Shutdown = Shutdown {
+    disconnect: || {
+        let (tx, rx) = async_std::channel::bounded::<usize>(FULL);
+        (
+            Box::new(tx),
+            Box::new(move || {
+                futures::executor::block_on(async { while rx.recv().await.is_ok() {} })
+            }),
+        )
+    },
+    full: || {
+        let (tx, rx) = async_std::channel::bounded(FULL);
+        for i in 0..FULL {
+            tx.try_send(i).unwrap();
+        }
+        (Box::new(tx), Box::new(rx))
+    },
+};
+
+const TOKIO_UNBOUNDED: Shutdown = Shutdown {
+    disconnect: || {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<usize>();
+        (
+            Box::new(tx),
+            Box::new(move || {
+                futures::executor::block_on(async { while rx.recv().await.is_some() {} })
+            }),
+        )
+    },
+    full: || {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        for i in 0..FULL {
+            tx.send(i).unwrap();
+        }
+        (Box::new(tx), Box::new(rx))
+    },
+};
+
+const TOKIO_BOUNDED: Shutdown = Shutdown {
+    disconnect: || {
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<usize>(FULL);
+        (
+            Box::new(tx),
+            Box::new(move || {
+                futures::executor::block_on(async { while rx.recv().await.is_some() {} })
+            }),
+        )
+    },
+    full: || {
+        let (tx, rx) = tokio::sync::mpsc::channel(FULL);
+        for i in 0..FULL {
+            tx.try_send(i).unwrap();
+        }
+        (Box::new(tx), Box::new(rx))
+    },
+};
+
+const ASYNC_CHANNEL_UNBOUNDED: Shutdown = Shutdown {
+    disconnect: || {
+        let (tx, rx) = async_channel::unbounded::<usize>();
+        (
+            Box::new(tx),
+            Box::new(move || {
+                futures::executor::block_on(async { while rx.recv().await.is_ok() {} })
+            }),
+        )
+    },
+    full: || {
+        let (tx, rx) = async_channel::unbounded();
+        for i in 0..FULL {
+            tx.try_send(i).unwrap();
+        }
+        (Box::new(tx), Box::new(rx))
+    },
+};
+
+const ASYNC_CHANNEL_BOUNDED: Shutdown = Shutdown {
+    disconnect: || {
+        let (tx, rx) = async_channel::bounded::<usize>(FULL);
+        (
+            Box::new(tx),
+            Box::new(move || {
+                futures::executor::block_on(async { while rx.recv().await.is_ok() {} })
+            }),
+        )
+    },
+    full: || {
+        let (tx, rx) = async_channel::bounded(FULL);
+        for i in 0..FULL {
+            tx.try_send(i).unwrap();
+        }
+        (Box::new(tx), Box::new(rx))
+    },
+};
+
+const FUTURES_UNBOUNDED: Shutdown = Shutdown {
+    disconnect: || {
+        let (tx, mut rx) = futures::channel::mpsc::unbounded::<usize>();
+        (
+            Box::new(tx),
+            Box::new(move || {
+                futures::executor::block_on(async { while rx.next().await.is_some() {} })
+            }),
+        )
+    },
+    full: || {
+        let (tx, rx) = futures::channel::mpsc::unbounded();
+        for i in 0..FULL {
+            tx.unbounded_send(i).unwrap();
+        }
+        (Box::new(tx), Box::new(rx))
+    },
+};
+
+const FUTURES_BOUNDED: Shutdown = Shutdown {
+    disconnect: || {
+        let (tx, mut rx) = futures::channel::mpsc::channel::<usize>(FULL);
+        (
+            Box::new(tx),
+            Box::new(move || {
+                futures::executor::block_on(async { while rx.next().await.is_some() {} })
+            }),
+        )
+    },
+    full: || {
+        let (mut tx, rx) = futures::channel::mpsc::channel(FULL);
+        for i in 0..FULL {
+            tx.try_send(i).unwrap();
+        }
+        (Box::new(tx), Box::new(rx))
+    },
+};
@@ -0,0 +1,195 @@
+use async_barrier::Barrier;
+use async_channel::{Receiver, Sender};
+use async_lock::Mutex;
+use smol::{Executor, Task};
+use std::sync::{Arc, Once, OnceLock};
+
+static EXECUTOR: OnceLock<Executor<'static>> = OnceLock::new();
+static WORKERS: Once = Once::new();
+
+/// The executor shared by every scenario in this module.
+///
+/// `smol::spawn` runs on a single thread unless `SMOL_THREADS` is set,
+/// so spawn one worker per hardware thread here to be comparable with the
+/// multi-threaded executors of async_std and tokio.
+fn executor() -> &'static Executor<'static> {
+    let ex = EXECUTOR.get_or_init(Executor::new);
+    WORKERS.call_once(|| {
+        let n = std::thread::available_parallelism().map_or(1, |n| n.get());
+        for _ in 0..n {
+            std::thread::spawn(move || smol::block_on(ex.run(smol::future::pending::<()>())));
+        }
+    });
+    ex
+}
+
+fn spawn<T: Send + 'static>(
+    future: impl std::future::Future<Output = T> + Send + 'static,
+) -> Task<T> {
+    executor().spawn(future)
+}
+
+pub struct OneToOneSmol {
+    handler: Vec<Task<usize>>,
+    barrier: Arc<Barrier>,
+}
+
+impl OneToOneSmol {
+    pub async fn start(&mut self) {
+        self.barrier.wait().await;
+        let v = std::mem::take(&mut self.handler);
+        for th in v {
+            th.await;
+        }
+    }
+
+    pub fn new(n: usize, count: usize, f: fn() -> (Sender<usize>, Receiver<usize>)) -> Self {
+        let mut v = Vec::new();
+        let barrier = async_barrier::Barrier::new(2 * n + 1);
+        let barrier = Arc::new(barrier);
+
+        for _ in 0..n {
+            let (tx, rx) = f();
+
+            // Create a sender.
+            let bar = barrier.clone();
+            let th = spawn(async move {
+                bar.wait().await;
+                for _ in 0..count {
+                    tx.send(1).await.unwrap();
+                }
+                0
+            });
+            v.push(th);
+
+            // Create a receiver.
+            let bar = barrier.clone();
+            let th = spawn(async move {
+                bar.wait().await;
+                let mut cnt = 0;
+                for _ in 0..count {
+                    let n = rx.recv().await.unwrap();
+                    cnt += n;
+                }
+                cnt
+            });
+            v.push(th);
+        }
+
+        OneToOneSmol {
+            handler: v,
+            barrier,
+        }
+    }
+}
+
+pub async fn new_one_to_one_unbounded(n: usize, count: usize) -> OneToOneSmol {
+    OneToOneSmol::new(n, count, async_channel::unbounded)
+}
+
+pub async fn new_one_to_one_bounded(n: usize, count: usize) -> OneToOneSmol {
+    fn mkch() -> (Sender<usize>, Receiver<usize>) {
+        async_channel::bounded(1024)
+    }
+    OneToOneSmol::new(n, count, mkch)
+}
+
+pub struct ManyToOneSmol {
+    handler: Vec<Task<usize>>,
+    barrier: Arc<Barrier>,
+}
+
+impl ManyToOneSmol {
+    pub async fn start(&mut self) {
+        self.barrier.wait().await;
+        let v = std::mem::take(&mut self.handler);
+        for th in v {
+            th.await;
+        }
+    }
+
+    pub fn new(n: usize, count: usize, f: fn() -> (Sender<usize>, Receiver<usize>)) -> Self {
+        let mut v = Vec::new();
+        let barrier = async_barrier::Barrier::new(n + 2);
+        let barrier = Arc::new(barrier);
+        let (tx, rx) = f();
+
+        // Create a receiver.
+        let bar = barrier.clone();
+        let th = spawn(async move {
+            bar.wait().await;
+            let mut cnt = 0;
+            for _ in 0..(count * n) {
+                let n = rx.recv().await.unwrap();
+                cnt += n;
+            }
+            cnt
+        });
+        v.push(th);
+
+        for _ in 0..n {
+            // Create a sender.
+            let bar = barrier.clone();
+            let ch = tx.clone();
+            let th = spawn(async move {
+                bar.wait().await;
+                for _ in 0..count {
+                    ch.send(1).await.unwrap();
+                }
+                0
+            });
+            v.push(th);
+        }
+
+        ManyToOneSmol {
+            handler: v,
+            barrier,
+        }
+    }
+}
+
+pub async fn new_many_to_one_bounded(n: usize, count: usize) -> ManyToOneSmol {
+    fn mkch() -> (Sender<usize>, Receiver<usize>) {
+        async_channel::bounded(1024)
+    }
+    ManyToOneSmol::new(n, count, mkch)
+}
+
+pub struct MutexBench {
+    handler: Vec<Task<()>>,
+    barrier: Arc<Barrier>,
+}
+
+impl MutexBench {
+    pub fn new(n: usize, count: usize) -> Self {
+        let mut v = Vec::new();
+        let barrier = Arc::new(Barrier::new(n + 1));
+        let shared = Arc::new(Mutex::new(0));
+
+        for _ in 0..n {
+            let bar = barrier.clone();
+            let n = shared.clone();
+            let th = spawn(async move {
+                bar.wait().await;
+                for _ in 0..count {
+                    let mut guard = n.lock().await;
+                    *guard += 1;
+                }
+            });
+            v.push(th);
+        }
+
+        Self {
+            handler: v,
+            barrier,
+        }
+    }
+
+    pub async fn start(&mut self) {
+        self.barrier.wait().await;
+        let v = std::mem::take(&mut self.handler);
+        for th in v {
+            th.await;
+        }
+    }
+}
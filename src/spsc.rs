@@ -0,0 +1,280 @@
+//! A minimal lock-free bounded SPSC ring buffer.
+//!
+//! It is not meant to be used as a general purpose channel.
+//! It has no disconnection, and the producer simply spins when the buffer is full.
+//! It serves as a lower bound of the cost of passing a message between two threads,
+//! to which other channels are compared.
+
+use crossbeam::utils::{Backoff, CachePadded};
+use std::{
+    cell::{Cell, UnsafeCell},
+    mem::MaybeUninit,
+    sync::{
+        atomic::{fence, AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread::Thread,
+};
+
+/// How the consumer waits while the buffer is empty.
+#[derive(Debug, Clone, Copy)]
+pub enum Wait {
+    /// Busy-wait without ever leaving the CPU.
+    Spin,
+    /// Spin and yield for a while, and then park until the producer unparks it.
+    Park,
+}
+
+struct Inner<T> {
+    buf: Box<[UnsafeCell<MaybeUninit<T>>]>,
+    mask: usize,
+
+    /// Index of the next slot to pop, written by the consumer only.
+    head: CachePadded<AtomicUsize>,
+
+    /// Index of the next slot to push, written by the producer only.
+    tail: CachePadded<AtomicUsize>,
+
+    wait: Wait,
+    parked: CachePadded<AtomicBool>,
+    consumer: Mutex<Option<Thread>>,
+}
+
+// SAFETY: `Inner` is shared by exactly one `Producer` and one `Consumer`, neither of which is `Sync`.
+// Slots in `head..tail` are initialized and accessed only by the consumer,
+// and the other slots only by the producer.
+// A slot changes hands only through a release store of `tail` or `head`
+// and an acquire load of it on the other side, so the two never access a slot at the same time.
+// Values are moved from one thread to the other, hence `T: Send`.
+unsafe impl<T: Send> Sync for Inner<T> {}
+
+impl<T> Drop for Inner<T> {
+    fn drop(&mut self) {
+        let head = *self.head.get_mut();
+        let tail = *self.tail.get_mut();
+        let mut i = head;
+        while i != tail {
+            // SAFETY: `&mut self` means both ends have been dropped, so nothing else accesses the slots.
+            // Slots in `head..tail` were written and not yet popped, so they are initialized,
+            // and each is dropped once.
+            unsafe { self.buf[i & self.mask].get_mut().assume_init_drop() };
+            i = i.wrapping_add(1);
+        }
+    }
+}
+
+pub struct Producer<T> {
+    inner: Arc<Inner<T>>,
+    tail: Cell<usize>,
+    head_cache: Cell<usize>,
+}
+
+pub struct Consumer<T> {
+    inner: Arc<Inner<T>>,
+    head: Cell<usize>,
+    tail_cache: Cell<usize>,
+}
+
+/// Create a ring buffer whose capacity is `capacity` rounded up to a power of two.
+pub fn channel<T>(capacity: usize, wait: Wait) -> (Producer<T>, Consumer<T>) {
+    let capacity = capacity.max(1).next_power_of_two();
+    let buf = (0..capacity)
+        .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+        .collect();
+
+    let inner = Arc::new(Inner {
+        buf,
+        mask: capacity - 1,
+        head: CachePadded::new(AtomicUsize::new(0)),
+        tail: CachePadded::new(AtomicUsize::new(0)),
+        wait,
+        parked: CachePadded::new(AtomicBool::new(false)),
+        consumer: Mutex::new(None),
+    });
+
+    (
+        Producer {
+            inner: inner.clone(),
+            tail: Cell::new(0),
+            head_cache: Cell::new(0),
+        },
+        Consumer {
+            inner,
+            head: Cell::new(0),
+            tail_cache: Cell::new(0),
+        },
+    )
+}
+
+impl<T> Producer<T> {
+    pub fn try_push(&self, x: T) -> Result<(), T> {
+        let inner = &*self.inner;
+        let tail = self.tail.get();
+
+        if tail.wrapping_sub(self.head_cache.get()) > inner.mask {
+            self.head_cache.set(inner.head.load(Ordering::Acquire));
+            if tail.wrapping_sub(self.head_cache.get()) > inner.mask {
+                return Err(x);
+            }
+        }
+
+        // SAFETY: `tail - head <= mask` as checked above, so the slot is not in `head..tail`
+        // and the consumer does not access it until the store of `tail` below publishes it.
+        // Its previous value, if any, was moved out by the consumer before the `head` we loaded,
+        // so overwriting it does not leak or drop anything.
+        unsafe { (*inner.buf[tail & inner.mask].get()).write(x) };
+        let tail = tail.wrapping_add(1);
+        inner.tail.store(tail, Ordering::Release);
+        self.tail.set(tail);
+
+        if let Wait::Park = inner.wait {
+            // Pairs with the fence in `Consumer::pop`, so that either the
+            // consumer sees the new tail or we see `parked`.
+            fence(Ordering::SeqCst);
+            if inner.parked.load(Ordering::Relaxed) && inner.parked.swap(false, Ordering::Relaxed) {
+                if let Some(th) = inner.consumer.lock().unwrap().as_ref() {
+                    th.unpark();
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Push `x`, spinning while the buffer is full.
+    pub fn push(&self, mut x: T) {
+        let backoff = Backoff::new();
+        loop {
+            match self.try_push(x) {
+                Ok(()) => return,
+                Err(y) => x = y,
+            }
+            backoff.snooze();
+        }
+    }
+}
+
+impl<T> Consumer<T> {
+    pub fn try_pop(&self) -> Option<T> {
+        let inner = &*self.inner;
+        let head = self.head.get();
+
+        if head == self.tail_cache.get() {
+            self.tail_cache.set(inner.tail.load(Ordering::Acquire));
+            if head == self.tail_cache.get() {
+                return None;
+            }
+        }
+
+        // SAFETY: `head != tail` as checked above, so the slot is in `head..tail`.
+        // The producer initialized it before its release store of `tail`, which we loaded with acquire,
+        // and does not write it again until the store of `head` below hands it back.
+        // `head` then moves past it, so the value is read out once.
+        let x = unsafe { (*inner.buf[head & inner.mask].get()).assume_init_read() };
+        let head = head.wrapping_add(1);
+        inner.head.store(head, Ordering::Release);
+        self.head.set(head);
+
+        Some(x)
+    }
+
+    /// Pop a value, waiting according to `Wait` while the buffer is empty.
+    pub fn pop(&self) -> T {
+        match self.inner.wait {
+            Wait::Spin => loop {
+                if let Some(x) = self.try_pop() {
+                    return x;
+                }
+                std::hint::spin_loop();
+            },
+            Wait::Park => {
+                let backoff = Backoff::new();
+                loop {
+                    if let Some(x) = self.try_pop() {
+                        return x;
+                    }
+
+                    if !backoff.is_completed() {
+                        backoff.snooze();
+                        continue;
+                    }
+
+                    let inner = &*self.inner;
+                    *inner.consumer.lock().unwrap() = Some(std::thread::current());
+                    inner.parked.store(true, Ordering::Relaxed);
+                    fence(Ordering::SeqCst);
+
+                    if let Some(x) = self.try_pop() {
+                        inner.parked.store(false, Ordering::Relaxed);
+                        return x;
+                    }
+
+                    std::thread::park();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraparound() {
+        let (tx, rx) = channel(4, Wait::Spin);
+
+        for i in 0..4 {
+            assert!(tx.try_push(i).is_ok());
+        }
+        assert_eq!(tx.try_push(4), Err(4));
+
+        // Indices go around the buffer many times.
+        for i in 0..100 {
+            assert_eq!(rx.try_pop(), Some(i));
+            tx.try_push(i + 4).unwrap();
+        }
+        for i in 100..104 {
+            assert_eq!(rx.try_pop(), Some(i));
+        }
+        assert_eq!(rx.try_pop(), None);
+    }
+
+    #[test]
+    fn drop_unconsumed() {
+        let item = Arc::new(());
+        let (tx, rx) = channel(4, Wait::Spin);
+
+        // Wrap around first, so that unconsumed items straddle the end of the buffer.
+        for _ in 0..3 {
+            tx.try_push(item.clone()).unwrap();
+            rx.try_pop().unwrap();
+        }
+        for _ in 0..4 {
+            tx.try_push(item.clone()).unwrap();
+        }
+        drop(rx.try_pop());
+        assert_eq!(Arc::strong_count(&item), 4);
+
+        drop(tx);
+        drop(rx);
+        assert_eq!(Arc::strong_count(&item), 1);
+    }
+
+    #[test]
+    fn park_across_threads() {
+        const COUNT: usize = 100_000;
+        let (tx, rx) = channel(16, Wait::Park);
+
+        let consumer = std::thread::spawn(move || {
+            for i in 0..COUNT {
+                assert_eq!(rx.pop(), i);
+            }
+        });
+
+        for i in 0..COUNT {
+            tx.push(i);
+        }
+        consumer.join().unwrap();
+    }
+}
@@ -1,31 +1,48 @@
+use crate::{
+    latency::Latency,
+    workload::{Bursts, Workload},
+};
 use std::{
     sync::{mpsc, Arc, Barrier, Mutex},
     thread::JoinHandle,
 };
 
+/// Send a message, shared by every backend run on `std::thread`.
+/// Spelling the boxed closures out in `fn() -> (Tx, Rx)` trips clippy's `type_complexity`.
+pub type Tx = Box<dyn Fn(usize) + Send>;
+
+/// Receive a message.
+pub type Rx = Box<dyn Fn() -> usize + Send>;
+
 pub struct OneToOne {
     handler: Vec<JoinHandle<usize>>,
     barrier: Arc<Barrier>,
+    bursts: Bursts,
 }
 
 impl OneToOne {
-    pub fn new(
-        n: usize,
-        mkch: fn() -> (Box<dyn Fn(usize) + Send>, Box<dyn Fn() -> usize + Send>),
-    ) -> Self {
+    /// n pairs of a sender and a receiver, exchanging `count` messages each.
+    pub fn new(n: usize, count: usize, mkch: fn() -> (Tx, Rx)) -> Self {
+        Self::new_with(n, count, mkch, Workload::Continuous)
+    }
+
+    pub fn new_with(n: usize, count: usize, mkch: fn() -> (Tx, Rx), workload: Workload) -> Self {
         let mut v = Vec::new();
 
         let barrier = Arc::new(Barrier::new(n * 2 + 1));
+        let bursts = Bursts::new(workload);
 
         for _ in 0..n {
             let (tx, rx) = mkch();
 
             // Create a sender.
             let bar = barrier.clone();
+            let mut stamp = bursts.stamp();
             let th = std::thread::spawn(move || {
                 bar.wait();
-                for _ in 0..crate::MAX_COUNT {
-                    tx(1);
+                for i in 0..count {
+                    tx(stamp.message(i));
+                    workload.pace(i);
                 }
                 0
             });
@@ -33,14 +50,14 @@ impl OneToOne {
 
             // Create a receiver.
             let bar = barrier.clone();
+            let mut drain = bursts.drain();
             let th = std::thread::spawn(move || {
                 bar.wait();
-                let mut cnt = 0;
-                for _ in 0..crate::MAX_COUNT {
-                    let n = rx();
-                    cnt += n;
+                for _ in 0..count {
+                    drain.received(rx());
                 }
-                cnt
+                drain.finish();
+                0
             });
             v.push(th);
         }
@@ -48,7 +65,26 @@ impl OneToOne {
         Self {
             handler: v,
             barrier,
+            bursts,
+        }
+    }
+
+    /// Pin the sender and the receiver of each pair to the given CPUs.
+    /// On error, the threads are released and joined, so that none is left waiting to start.
+    #[cfg(target_os = "linux")]
+    pub fn pin(&mut self, cpus: &[(usize, usize)]) -> std::io::Result<()> {
+        let pinned = self
+            .handler
+            .chunks(2)
+            .zip(cpus)
+            .try_for_each(|(th, (tx, rx))| {
+                crate::affinity::pin(&th[0], *tx)?;
+                crate::affinity::pin(&th[1], *rx)
+            });
+        if pinned.is_err() {
+            self.start();
         }
+        pinned
     }
 
     pub fn start(&mut self) {
@@ -58,10 +94,15 @@ impl OneToOne {
             th.join().unwrap();
         }
     }
+
+    /// Latency of draining every burst of the last run, if the workload is bursty.
+    pub fn drain_latency(&self) -> Option<Latency> {
+        self.bursts.latency()
+    }
 }
 
-pub fn new_one_to_one_channel(n: usize) -> OneToOne {
-    fn mkch() -> (Box<dyn Fn(usize) + Send>, Box<dyn Fn() -> usize + Send>) {
+pub fn new_one_to_one_channel(n: usize, count: usize) -> OneToOne {
+    fn mkch() -> (Tx, Rx) {
         let (tx, rx) = mpsc::channel();
         (
             Box::new(move |x| tx.send(x).unwrap()),
@@ -69,11 +110,15 @@ pub fn new_one_to_one_channel(n: usize) -> OneToOne {
         )
     }
 
-    OneToOne::new(n, mkch)
+    OneToOne::new(n, count, mkch)
 }
 
-pub fn new_one_to_one_sync_channel(n: usize) -> OneToOne {
-    fn mkch() -> (Box<dyn Fn(usize) + Send>, Box<dyn Fn() -> usize + Send>) {
+pub fn new_one_to_one_sync_channel(n: usize, count: usize) -> OneToOne {
+    new_one_to_one_sync_channel_with(n, count, Workload::Continuous)
+}
+
+pub fn new_one_to_one_sync_channel_with(n: usize, count: usize, workload: Workload) -> OneToOne {
+    fn mkch() -> (Tx, Rx) {
         let (tx, rx) = mpsc::sync_channel(1024);
         (
             Box::new(move |x| tx.send(x).unwrap()),
@@ -81,42 +126,50 @@ pub fn new_one_to_one_sync_channel(n: usize) -> OneToOne {
         )
     }
 
-    OneToOne::new(n, mkch)
+    OneToOne::new_with(n, count, mkch, workload)
 }
 
 pub struct ManyToOne {
     handler: Vec<JoinHandle<usize>>,
     barrier: Arc<Barrier>,
+    bursts: Bursts,
 }
 
 impl ManyToOne {
-    pub fn new(tx: Vec<Box<dyn Fn(usize) + Send>>, rx: Box<dyn Fn() -> usize + Send>) -> Self {
+    /// Each sender sends `count` messages, and the receiver receives all of them.
+    pub fn new(count: usize, tx: Vec<Tx>, rx: Rx) -> Self {
+        Self::new_with(count, tx, rx, Workload::Continuous)
+    }
+
+    pub fn new_with(count: usize, tx: Vec<Tx>, rx: Rx, workload: Workload) -> Self {
         let mut v = Vec::new();
         let barrier = Arc::new(Barrier::new(tx.len() + 2));
+        let bursts = Bursts::new(workload);
 
-        let max_count = crate::MAX_COUNT / 10;
         let tx_len = tx.len();
 
         // Create a receiver.
         let bar = barrier.clone();
+        let mut drain = bursts.drain();
         let th = std::thread::spawn(move || {
             bar.wait();
-            let mut cnt = 0;
-            for _ in 0..(max_count * tx_len) {
-                let n = rx();
-                cnt += n;
+            for _ in 0..(count * tx_len) {
+                drain.received(rx());
             }
-            cnt
+            drain.finish();
+            0
         });
         v.push(th);
 
         for ch in tx {
             // Create a sender.
             let bar = barrier.clone();
+            let mut stamp = bursts.stamp();
             let th = std::thread::spawn(move || {
                 bar.wait();
-                for _ in 0..max_count {
-                    ch(1);
+                for i in 0..count {
+                    ch(stamp.message(i));
+                    workload.pace(i);
                 }
                 0
             });
@@ -126,6 +179,7 @@ impl ManyToOne {
         Self {
             handler: v,
             barrier,
+            bursts,
         }
     }
 
@@ -136,11 +190,20 @@ impl ManyToOne {
             th.join().unwrap();
         }
     }
+
+    /// Latency of draining every burst of the last run, if the workload is bursty.
+    pub fn drain_latency(&self) -> Option<Latency> {
+        self.bursts.latency()
+    }
+}
+
+pub fn new_many_to_one_sync_channel(n: usize, count: usize) -> ManyToOne {
+    new_many_to_one_sync_channel_with(n, count, Workload::Continuous)
 }
 
-pub fn new_many_to_one_sync_channel(n: usize) -> ManyToOne {
+pub fn new_many_to_one_sync_channel_with(n: usize, count: usize, workload: Workload) -> ManyToOne {
     let (tx, rx) = mpsc::sync_channel(1024);
-    let mut v = Vec::<Box<dyn Fn(usize) + Send>>::new();
+    let mut v = Vec::<Tx>::new();
 
     for _ in 0..n {
         let ch = tx.clone();
@@ -149,7 +212,7 @@ pub fn new_many_to_one_sync_channel(n: usize) -> ManyToOne {
         }));
     }
 
-    ManyToOne::new(v, Box::new(move || rx.recv().unwrap()))
+    ManyToOne::new_with(count, v, Box::new(move || rx.recv().unwrap()), workload)
 }
 
 pub struct MutexBench {
@@ -158,7 +221,7 @@ pub struct MutexBench {
 }
 
 impl MutexBench {
-    pub fn new(n: usize) -> Self {
+    pub fn new(n: usize, count: usize) -> Self {
         let mut v = Vec::new();
         let barrier = Arc::new(Barrier::new(n + 1));
         let shared = Arc::new(Mutex::new(0));
@@ -168,7 +231,7 @@ impl MutexBench {
             let n = shared.clone();
             let th = std::thread::spawn(move || {
                 bar.wait();
-                for _ in 0..crate::MAX_COUNT {
+                for _ in 0..count {
                     let mut guard = n.lock().unwrap();
                     *guard += 1;
                 }
@@ -197,7 +260,7 @@ pub struct MutexBenchPackingLot {
 }
 
 impl MutexBenchPackingLot {
-    pub fn new(n: usize) -> Self {
+    pub fn new(n: usize, count: usize) -> Self {
         let mut v = Vec::new();
         let barrier = Arc::new(Barrier::new(n + 1));
         let shared = Arc::new(parking_lot::Mutex::new(0));
@@ -207,7 +270,7 @@ impl MutexBenchPackingLot {
             let n = shared.clone();
             let th = std::thread::spawn(move || {
                 bar.wait();
-                for _ in 0..crate::MAX_COUNT {
+                for _ in 0..count {
                     let mut guard = n.lock();
                     *guard += 1;
                 }
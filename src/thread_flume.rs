@@ -1,8 +1,10 @@
-use crate::std_thread::{ManyToOne, OneToOne};
-use flume;
+use crate::{
+    std_thread::{ManyToOne, OneToOne, Rx, Tx},
+    workload::Workload,
+};
 
-pub fn new_one_to_one_unbounded(n: usize) -> OneToOne {
-    fn mkch() -> (Box<dyn Fn(usize) + Send>, Box<dyn Fn() -> usize + Send>) {
+pub fn new_one_to_one_unbounded(n: usize, count: usize) -> OneToOne {
+    fn mkch() -> (Tx, Rx) {
         let (tx, rx) = flume::unbounded();
         (
             Box::new(move |x| tx.send(x).unwrap()),
@@ -10,11 +12,15 @@ pub fn new_one_to_one_unbounded(n: usize) -> OneToOne {
         )
     }
 
-    OneToOne::new(n, mkch)
+    OneToOne::new(n, count, mkch)
 }
 
-pub fn new_one_to_one_bounded(n: usize) -> OneToOne {
-    fn mkch() -> (Box<dyn Fn(usize) + Send>, Box<dyn Fn() -> usize + Send>) {
+pub fn new_one_to_one_bounded(n: usize, count: usize) -> OneToOne {
+    new_one_to_one_bounded_with(n, count, Workload::Continuous)
+}
+
+pub fn new_one_to_one_bounded_with(n: usize, count: usize, workload: Workload) -> OneToOne {
+    fn mkch() -> (Tx, Rx) {
         let (tx, rx) = flume::bounded(1024);
         (
             Box::new(move |x| tx.send(x).unwrap()),
@@ -22,12 +28,16 @@ pub fn new_one_to_one_bounded(n: usize) -> OneToOne {
         )
     }
 
-    OneToOne::new(n, mkch)
+    OneToOne::new_with(n, count, mkch, workload)
+}
+
+pub fn new_many_to_one_bounded(n: usize, count: usize) -> ManyToOne {
+    new_many_to_one_bounded_with(n, count, Workload::Continuous)
 }
 
-pub fn new_many_to_one_bounded(n: usize) -> ManyToOne {
+pub fn new_many_to_one_bounded_with(n: usize, count: usize, workload: Workload) -> ManyToOne {
     let (tx, rx) = flume::bounded(1024);
-    let mut v = Vec::<Box<dyn Fn(usize) + Send>>::new();
+    let mut v = Vec::<Tx>::new();
 
     for _ in 0..n {
         let ch = tx.clone();
@@ -36,5 +46,37 @@ pub fn new_many_to_one_bounded(n: usize) -> ManyToOne {
         }));
     }
 
-    ManyToOne::new(v, Box::new(move || rx.recv().unwrap()))
+    ManyToOne::new_with(count, v, Box::new(move || rx.recv().unwrap()), workload)
+}
+
+/// A receiver waits on k channels, each of which has a sender, with `flume::Selector`.
+pub fn new_select_bounded(k: usize, count: usize) -> ManyToOne {
+    let mut v = Vec::<Tx>::new();
+    let mut txs = Vec::new();
+    let mut rxs = Vec::new();
+
+    for _ in 0..k {
+        let (tx, rx) = flume::bounded(1024);
+        let ch = tx.clone();
+        v.push(Box::new(move |msg| {
+            ch.send(msg).unwrap();
+        }));
+        txs.push(tx);
+        rxs.push(rx);
+    }
+
+    ManyToOne::new(
+        count,
+        v,
+        Box::new(move || {
+            // Keep the senders alive so that no channel is disconnected while selecting.
+            let _ = &txs;
+
+            rxs.iter()
+                .fold(flume::Selector::new(), |sel, rx| {
+                    sel.recv(rx, |n| n.unwrap())
+                })
+                .wait()
+        }),
+    )
 }
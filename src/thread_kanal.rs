@@ -0,0 +1,39 @@
+use crate::std_thread::{ManyToOne, OneToOne, Rx, Tx};
+
+pub fn new_one_to_one_unbounded(n: usize, count: usize) -> OneToOne {
+    fn mkch() -> (Tx, Rx) {
+        let (tx, rx) = kanal::unbounded();
+        (
+            Box::new(move |x| tx.send(x).unwrap()),
+            Box::new(move || rx.recv().unwrap()),
+        )
+    }
+
+    OneToOne::new(n, count, mkch)
+}
+
+pub fn new_one_to_one_bounded(n: usize, count: usize) -> OneToOne {
+    fn mkch() -> (Tx, Rx) {
+        let (tx, rx) = kanal::bounded(1024);
+        (
+            Box::new(move |x| tx.send(x).unwrap()),
+            Box::new(move || rx.recv().unwrap()),
+        )
+    }
+
+    OneToOne::new(n, count, mkch)
+}
+
+pub fn new_many_to_one_bounded(n: usize, count: usize) -> ManyToOne {
+    let (tx, rx) = kanal::bounded(1024);
+    let mut v = Vec::<Tx>::new();
+
+    for _ in 0..n {
+        let ch = tx.clone();
+        v.push(Box::new(move |msg| {
+            ch.send(msg).unwrap();
+        }));
+    }
+
+    ManyToOne::new(count, v, Box::new(move || rx.recv().unwrap()))
+}
@@ -0,0 +1,36 @@
+//! `ringbuf` is a lock-free SPSC ring buffer without blocking operations,
+//! so both ends wait with `crossbeam::utils::Backoff`, which spins first
+//! and then yields the thread.
+//! Only one-to-one scenarios are provided because it has a single producer.
+
+use crate::std_thread::{OneToOne, Rx, Tx};
+use crossbeam::utils::Backoff;
+use ringbuf::{traits::*, HeapRb};
+use std::cell::RefCell;
+
+pub fn new_one_to_one_bounded(n: usize, count: usize) -> OneToOne {
+    fn mkch() -> (Tx, Rx) {
+        let (tx, rx) = HeapRb::<usize>::new(1024).split();
+        let tx = RefCell::new(tx);
+        let rx = RefCell::new(rx);
+        (
+            Box::new(move |x| {
+                let backoff = Backoff::new();
+                while tx.borrow_mut().try_push(x).is_err() {
+                    backoff.snooze();
+                }
+            }),
+            Box::new(move || {
+                let backoff = Backoff::new();
+                loop {
+                    if let Some(x) = rx.borrow_mut().try_pop() {
+                        return x;
+                    }
+                    backoff.snooze();
+                }
+            }),
+        )
+    }
+
+    OneToOne::new(n, count, mkch)
+}
@@ -0,0 +1,22 @@
+use crate::{
+    spsc::{self, Wait},
+    std_thread::{OneToOne, Rx, Tx},
+};
+
+pub fn new_one_to_one_spin(n: usize, count: usize) -> OneToOne {
+    fn mkch() -> (Tx, Rx) {
+        let (tx, rx) = spsc::channel(1024, Wait::Spin);
+        (Box::new(move |x| tx.push(x)), Box::new(move || rx.pop()))
+    }
+
+    OneToOne::new(n, count, mkch)
+}
+
+pub fn new_one_to_one_park(n: usize, count: usize) -> OneToOne {
+    fn mkch() -> (Tx, Rx) {
+        let (tx, rx) = spsc::channel(1024, Wait::Park);
+        (Box::new(move |x| tx.push(x)), Box::new(move || rx.pop()))
+    }
+
+    OneToOne::new(n, count, mkch)
+}
@@ -0,0 +1,28 @@
+use crate::std_thread::{ManyToOne, OneToOne, Rx, Tx};
+use thingbuf::mpsc::blocking;
+
+pub fn new_one_to_one_bounded(n: usize, count: usize) -> OneToOne {
+    fn mkch() -> (Tx, Rx) {
+        let (tx, rx) = blocking::channel(1024);
+        (
+            Box::new(move |x| tx.send(x).unwrap()),
+            Box::new(move || rx.recv().unwrap()),
+        )
+    }
+
+    OneToOne::new(n, count, mkch)
+}
+
+pub fn new_many_to_one_bounded(n: usize, count: usize) -> ManyToOne {
+    let (tx, rx) = blocking::channel(1024);
+    let mut v = Vec::<Tx>::new();
+
+    for _ in 0..n {
+        let ch = tx.clone();
+        v.push(Box::new(move |msg| {
+            ch.send(msg).unwrap();
+        }));
+    }
+
+    ManyToOne::new(count, v, Box::new(move || rx.recv().unwrap()))
+}
@@ -1,5 +1,9 @@
+use crate::{
+    latency::Latency,
+    workload::{Bursts, Workload},
+};
 use async_barrier::Barrier;
-use std::sync::Arc;
+use std::{sync::Arc, task::Poll};
 use tokio::{
     sync::{mpsc, Mutex},
     task::JoinHandle,
@@ -8,6 +12,7 @@ use tokio::{
 pub struct OneToOneTokio {
     handler: Vec<JoinHandle<usize>>,
     barrier: Arc<Barrier>,
+    bursts: Bursts,
 }
 
 impl OneToOneTokio {
@@ -19,7 +24,12 @@ impl OneToOneTokio {
         }
     }
 
-    pub fn new_unbounded(n: usize) -> Self {
+    /// Latency of draining every burst of the last run, if the workload is bursty.
+    pub fn drain_latency(&self) -> Option<Latency> {
+        self.bursts.latency()
+    }
+
+    pub fn new_unbounded(n: usize, count: usize) -> Self {
         let mut v = Vec::new();
         let barrier = async_barrier::Barrier::new(2 * n + 1);
         let barrier = Arc::new(barrier);
@@ -31,7 +41,7 @@ impl OneToOneTokio {
             let bar = barrier.clone();
             let th = tokio::task::spawn(async move {
                 bar.wait().await;
-                for n in 0..crate::MAX_COUNT {
+                for n in 0..count {
                     tx.send(1).unwrap();
                     if n & 0xff == 0 {
                         tokio::task::yield_now().await;
@@ -46,7 +56,7 @@ impl OneToOneTokio {
             let th = tokio::task::spawn(async move {
                 bar.wait().await;
                 let mut cnt = 0;
-                for _ in 0..crate::MAX_COUNT {
+                for _ in 0..count {
                     let n = rx.recv().await.unwrap();
                     cnt += n;
                 }
@@ -58,22 +68,76 @@ impl OneToOneTokio {
         Self {
             handler: v,
             barrier,
+            bursts: Bursts::default(),
         }
     }
 
-    pub fn new_bounded(n: usize) -> Self {
+    pub fn new_bounded(n: usize, count: usize) -> Self {
+        Self::new_bounded_with(n, count, Workload::Continuous)
+    }
+
+    pub fn new_bounded_with(n: usize, count: usize, workload: Workload) -> Self {
         let mut v = Vec::new();
         let barrier = async_barrier::Barrier::new(2 * n + 1);
         let barrier = Arc::new(barrier);
+        let bursts = Bursts::new(workload);
 
         for _ in 0..n {
             let (tx, mut rx) = mpsc::channel(1024);
 
             // Create a sender.
             let bar = barrier.clone();
+            let mut stamp = bursts.stamp();
+            let th = tokio::task::spawn(async move {
+                bar.wait().await;
+                for n in 0..count {
+                    tx.send(stamp.message(n)).await.unwrap();
+                    if n & 0xff == 0 {
+                        tokio::task::yield_now().await;
+                    }
+                    if let Some(gap) = workload.gap_after(n) {
+                        tokio::time::sleep(gap).await;
+                    }
+                }
+                0
+            });
+            v.push(th);
+
+            // Create a receiver.
+            let bar = barrier.clone();
+            let mut drain = bursts.drain();
             let th = tokio::task::spawn(async move {
                 bar.wait().await;
-                for n in 0..crate::MAX_COUNT {
+                for _ in 0..count {
+                    drain.received(rx.recv().await.unwrap());
+                }
+                drain.finish();
+                0
+            });
+            v.push(th);
+        }
+
+        Self {
+            handler: v,
+            barrier,
+            bursts,
+        }
+    }
+
+    /// Receivers drain up to `batch::BATCH` messages at once with `recv_many`.
+    pub fn new_bounded_recv_many(n: usize, count: usize) -> Self {
+        let mut v = Vec::new();
+        let barrier = async_barrier::Barrier::new(2 * n + 1);
+        let barrier = Arc::new(barrier);
+
+        for _ in 0..n {
+            let (tx, mut rx) = mpsc::channel(1024);
+
+            // Create a sender.
+            let bar = barrier.clone();
+            let th = tokio::task::spawn(async move {
+                bar.wait().await;
+                for n in 0..count {
                     tx.send(1).await.unwrap();
                     if n & 0xff == 0 {
                         tokio::task::yield_now().await;
@@ -87,10 +151,13 @@ impl OneToOneTokio {
             let bar = barrier.clone();
             let th = tokio::task::spawn(async move {
                 bar.wait().await;
+                let mut buf = Vec::with_capacity(crate::batch::BATCH);
+                let mut received = 0;
                 let mut cnt = 0;
-                for _ in 0..crate::MAX_COUNT {
-                    let n = rx.recv().await.unwrap();
-                    cnt += n;
+                while received < count {
+                    buf.clear();
+                    received += rx.recv_many(&mut buf, crate::batch::BATCH).await;
+                    cnt += buf.iter().sum::<usize>();
                 }
                 cnt
             });
@@ -100,21 +167,35 @@ impl OneToOneTokio {
         Self {
             handler: v,
             barrier,
+            bursts: Bursts::default(),
         }
     }
 }
 
-pub async fn new_one_to_one_unbounded(n: usize) -> OneToOneTokio {
-    OneToOneTokio::new_unbounded(n)
+pub async fn new_one_to_one_unbounded(n: usize, count: usize) -> OneToOneTokio {
+    OneToOneTokio::new_unbounded(n, count)
 }
 
-pub async fn new_one_to_one_bounded(n: usize) -> OneToOneTokio {
-    OneToOneTokio::new_bounded(n)
+pub async fn new_one_to_one_bounded(n: usize, count: usize) -> OneToOneTokio {
+    OneToOneTokio::new_bounded(n, count)
+}
+
+pub async fn new_one_to_one_recv_many(n: usize, count: usize) -> OneToOneTokio {
+    OneToOneTokio::new_bounded_recv_many(n, count)
+}
+
+pub async fn new_one_to_one_bounded_with(
+    n: usize,
+    count: usize,
+    workload: Workload,
+) -> OneToOneTokio {
+    OneToOneTokio::new_bounded_with(n, count, workload)
 }
 
 pub struct ManyToOneTokio {
     handler: Vec<JoinHandle<usize>>,
     barrier: Arc<Barrier>,
+    bursts: Bursts,
 }
 
 impl ManyToOneTokio {
@@ -126,34 +207,155 @@ impl ManyToOneTokio {
         }
     }
 
-    pub fn new_bounded(n: usize) -> Self {
+    /// Latency of draining every burst of the last run, if the workload is bursty.
+    pub fn drain_latency(&self) -> Option<Latency> {
+        self.bursts.latency()
+    }
+
+    pub fn new_bounded(n: usize, count: usize) -> Self {
+        Self::new_bounded_with(n, count, Workload::Continuous)
+    }
+
+    pub fn new_bounded_with(n: usize, count: usize, workload: Workload) -> Self {
         let mut v = Vec::new();
         let barrier = async_barrier::Barrier::new(n + 2);
         let barrier = Arc::new(barrier);
+        let bursts = Bursts::new(workload);
         let (tx, mut rx) = mpsc::channel(1024);
 
-        let max_count = crate::MAX_COUNT / 10;
+        // Create a receiver.
+        let bar = barrier.clone();
+        let mut drain = bursts.drain();
+        let th = tokio::task::spawn(async move {
+            bar.wait().await;
+            for _ in 0..(count * n) {
+                drain.received(rx.recv().await.unwrap());
+            }
+            drain.finish();
+            0
+        });
+        v.push(th);
+
+        for _ in 0..n {
+            // Create a sender.
+            let bar = barrier.clone();
+            let ch = tx.clone();
+            let mut stamp = bursts.stamp();
+            let th = tokio::task::spawn(async move {
+                bar.wait().await;
+                for n in 0..count {
+                    ch.send(stamp.message(n)).await.unwrap();
+                    if n & 0xff == 0 {
+                        tokio::task::yield_now().await;
+                    }
+                    if let Some(gap) = workload.gap_after(n) {
+                        tokio::time::sleep(gap).await;
+                    }
+                }
+                0
+            });
+            v.push(th);
+        }
+
+        Self {
+            handler: v,
+            barrier,
+            bursts,
+        }
+    }
+
+    /// A receiver waits on k channels, each of which has a sender.
+    ///
+    /// `tokio::select!` takes a fixed number of branches, so the receiver
+    /// polls every channel in turn as the macro does.
+    /// `new_select4` uses the macro itself with k = 4.
+    pub fn new_select(k: usize, count: usize) -> Self {
+        let mut v = Vec::new();
+        let barrier = async_barrier::Barrier::new(k + 2);
+        let barrier = Arc::new(barrier);
+        let mut txs = Vec::new();
+        let mut rxs = Vec::new();
+
+        for _ in 0..k {
+            let (tx, rx) = mpsc::channel(1024);
+            txs.push(tx);
+            rxs.push(rx);
+        }
+
+        for tx in txs.iter() {
+            // Create a sender.
+            let bar = barrier.clone();
+            let ch = tx.clone();
+            let th = tokio::task::spawn(async move {
+                bar.wait().await;
+                for n in 0..count {
+                    ch.send(1).await.unwrap();
+                    if n & 0xff == 0 {
+                        tokio::task::yield_now().await;
+                    }
+                }
+                0
+            });
+            v.push(th);
+        }
 
         // Create a receiver.
         let bar = barrier.clone();
         let th = tokio::task::spawn(async move {
+            // Keep the senders alive so that no channel is disconnected while selecting.
+            let _txs = txs;
+
             bar.wait().await;
             let mut cnt = 0;
-            for _ in 0..(max_count * n) {
-                let n = rx.recv().await.unwrap();
+            let mut next = 0;
+            for _ in 0..(count * k) {
+                let n = std::future::poll_fn(|cx| {
+                    for i in 0..k {
+                        let j = (next + i) % k;
+                        if let Poll::Ready(n) = rxs[j].poll_recv(cx) {
+                            next = j + 1;
+                            return Poll::Ready(n.unwrap());
+                        }
+                    }
+                    Poll::Pending
+                })
+                .await;
                 cnt += n;
             }
             cnt
         });
         v.push(th);
 
-        for _ in 0..n {
+        Self {
+            handler: v,
+            barrier,
+            bursts: Bursts::default(),
+        }
+    }
+}
+
+impl ManyToOneTokio {
+    /// A receiver waits on 4 channels with `tokio::select!`, each of which has a sender.
+    pub fn new_select4(count: usize) -> Self {
+        let mut v = Vec::new();
+        let barrier = async_barrier::Barrier::new(4 + 2);
+        let barrier = Arc::new(barrier);
+        let mut txs = Vec::new();
+        let mut rxs = Vec::new();
+
+        for _ in 0..4 {
+            let (tx, rx) = mpsc::channel(1024);
+            txs.push(tx);
+            rxs.push(rx);
+        }
+
+        for tx in txs.iter() {
             // Create a sender.
             let bar = barrier.clone();
             let ch = tx.clone();
             let th = tokio::task::spawn(async move {
                 bar.wait().await;
-                for n in 0..max_count {
+                for n in 0..count {
                     ch.send(1).await.unwrap();
                     if n & 0xff == 0 {
                         tokio::task::yield_now().await;
@@ -164,15 +366,54 @@ impl ManyToOneTokio {
             v.push(th);
         }
 
+        // Create a receiver.
+        let bar = barrier.clone();
+        let [mut a, mut b, mut c, mut d]: [mpsc::Receiver<usize>; 4] = rxs.try_into().unwrap();
+        let th = tokio::task::spawn(async move {
+            // Keep the senders alive so that no channel is disconnected while selecting.
+            let _txs = txs;
+
+            bar.wait().await;
+            let mut cnt = 0;
+            for _ in 0..(count * 4) {
+                let n = tokio::select! {
+                    n = a.recv() => n,
+                    n = b.recv() => n,
+                    n = c.recv() => n,
+                    n = d.recv() => n,
+                };
+                cnt += n.unwrap();
+            }
+            cnt
+        });
+        v.push(th);
+
         Self {
             handler: v,
             barrier,
+            bursts: Bursts::default(),
         }
     }
 }
 
-pub async fn new_many_to_one_bounded(n: usize) -> ManyToOneTokio {
-    ManyToOneTokio::new_bounded(n)
+pub async fn new_many_to_one_bounded(n: usize, count: usize) -> ManyToOneTokio {
+    ManyToOneTokio::new_bounded(n, count)
+}
+
+pub async fn new_many_to_one_bounded_with(
+    n: usize,
+    count: usize,
+    workload: Workload,
+) -> ManyToOneTokio {
+    ManyToOneTokio::new_bounded_with(n, count, workload)
+}
+
+pub async fn new_select_bounded(k: usize, count: usize) -> ManyToOneTokio {
+    ManyToOneTokio::new_select(k, count)
+}
+
+pub async fn new_select4_bounded(count: usize) -> ManyToOneTokio {
+    ManyToOneTokio::new_select4(count)
 }
 
 pub struct MutexBench {
@@ -181,7 +422,7 @@ pub struct MutexBench {
 }
 
 impl MutexBench {
-    pub fn new(n: usize) -> Self {
+    pub fn new(n: usize, count: usize) -> Self {
         let mut v = Vec::new();
         let barrier = Arc::new(Barrier::new(n + 1));
         let shared = Arc::new(Mutex::new(0));
@@ -191,7 +432,7 @@ impl MutexBench {
             let n = shared.clone();
             let th = tokio::task::spawn(async move {
                 bar.wait().await;
-                for _ in 0..crate::MAX_COUNT {
+                for _ in 0..count {
                     let mut guard = n.lock().await;
                     *guard += 1;
                 }
@@ -0,0 +1,158 @@
+//! How senders emit messages over time.
+
+use crate::latency::Latency;
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// The resolution of tokio's timer.
+/// A shorter gap would be rounded up for tokio only, so its bursts would not be comparable to others'.
+pub const MIN_GAP: Duration = Duration::from_millis(1);
+
+#[derive(Debug, Clone, Copy)]
+pub enum Workload {
+    /// Send as fast as possible.
+    Continuous,
+    /// Send `burst` messages, then stay idle for `gap`, which should be at least `MIN_GAP`.
+    ///
+    /// Receivers drain the channel during gaps and park,
+    /// so every burst exercises the paths to park and unpark them.
+    Bursty { burst: usize, gap: Duration },
+}
+
+impl Workload {
+    /// The idle time after the i-th message, if any.
+    pub fn gap_after(&self, i: usize) -> Option<Duration> {
+        match *self {
+            Workload::Continuous => None,
+            Workload::Bursty { burst, gap } => (i + 1).is_multiple_of(burst).then_some(gap),
+        }
+    }
+
+    /// Sleep the current thread after the i-th message, if needed.
+    pub fn pace(&self, i: usize) {
+        if let Some(gap) = self.gap_after(i) {
+            std::thread::sleep(gap);
+        }
+    }
+}
+
+impl fmt::Display for Workload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Workload::Continuous => write!(f, "continuous"),
+            Workload::Bursty { burst, gap } => write!(f, "bursts of {burst}, {gap:?} apart"),
+        }
+    }
+}
+
+/// The workload of a run, shared by its senders and receivers,
+/// which measure how long every burst takes to drain:
+/// from sending its first message until receiving its last.
+///
+/// Every message of a burst carries the time its first message was sent,
+/// as nanoseconds since the run was set up,
+/// so that a receiver can tell bursts of different senders apart.
+/// Continuous workloads send 1 as every message and measure nothing.
+#[derive(Clone)]
+pub struct Bursts {
+    workload: Workload,
+    epoch: Instant,
+    drains: Arc<Mutex<Vec<Duration>>>,
+}
+
+impl Bursts {
+    pub fn new(workload: Workload) -> Self {
+        Bursts {
+            workload,
+            epoch: Instant::now(),
+            drains: Arc::default(),
+        }
+    }
+
+    /// Stamps of the messages of a sender.
+    pub fn stamp(&self) -> Stamp {
+        Stamp {
+            workload: self.workload,
+            epoch: self.epoch,
+            current: 1,
+        }
+    }
+
+    /// Drains of the bursts of a receiver.
+    pub fn drain(&self) -> Drain {
+        Drain {
+            bursts: self.clone(),
+            pending: HashMap::new(),
+            samples: Vec::new(),
+        }
+    }
+
+    /// Latency of draining the bursts of the last run, if the workload is bursty.
+    pub fn latency(&self) -> Option<Latency> {
+        match self.workload {
+            Workload::Continuous => None,
+            Workload::Bursty { .. } => {
+                let v = std::mem::take(&mut *self.drains.lock().unwrap());
+                Some(Latency::from_samples(v))
+            }
+        }
+    }
+}
+
+impl Default for Bursts {
+    fn default() -> Self {
+        Self::new(Workload::Continuous)
+    }
+}
+
+pub struct Stamp {
+    workload: Workload,
+    epoch: Instant,
+    current: usize,
+}
+
+impl Stamp {
+    /// The i-th message of the sender.
+    pub fn message(&mut self, i: usize) -> usize {
+        if let Workload::Bursty { burst, .. } = self.workload {
+            if i.is_multiple_of(burst) {
+                self.current = self.epoch.elapsed().as_nanos() as usize;
+            }
+        }
+        self.current
+    }
+}
+
+pub struct Drain {
+    bursts: Bursts,
+    /// Messages yet to be received of each burst, by its stamp.
+    /// Bursts of two senders stamped with the same nanosecond are counted as one,
+    /// and the rest of them is never reported, which is rare enough to ignore.
+    pending: HashMap<usize, usize>,
+    samples: Vec<Duration>,
+}
+
+impl Drain {
+    pub fn received(&mut self, msg: usize) {
+        let Workload::Bursty { burst, .. } = self.bursts.workload else {
+            return;
+        };
+        let left = self.pending.entry(msg).or_insert(burst);
+        *left -= 1;
+        if *left == 0 {
+            self.pending.remove(&msg);
+            let sent = self.bursts.epoch + Duration::from_nanos(msg as u64);
+            self.samples.push(sent.elapsed());
+        }
+    }
+
+    /// Hand the drains of complete bursts to `Bursts::latency`.
+    /// A last burst shorter than the others is left out.
+    pub fn finish(self) {
+        self.bursts.drains.lock().unwrap().extend(self.samples);
+    }
+}